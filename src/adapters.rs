@@ -0,0 +1,434 @@
+// Iterator adapters
+// iterator.rs shows that the Iterator trait only asks for next(); everything else, like map and filter, is built on top of it. These adapters are built the same way, each one a struct that wraps another iterator and implements next().
+// IteratorExt adds them as methods to every iterator, so they chain like the ones in the standard library: (1..10).chunks(3).interleave(..).
+// Slices already have chunks, windows and dedup; these work on any iterator, including ones that are never collected into a Vec.
+
+use std::collections::VecDeque;
+
+pub fn iterator_adapters() {
+    let words = "the quick quick brown fox jumps over the the lazy dog";
+    println!("{:?}", words.split(' ').dedup().collect::<Vec<_>>());
+    println!("{:?}", (1..=7).chunks(3).collect::<Vec<_>>());
+    println!("{:?}", (1..=5).windows(2).map(|w| w[1] * w[0]).collect::<Vec<_>>());
+    println!("{:?}", ["a", "b", "c"].into_iter().interleave(["1", "2"]).collect::<String>());
+
+    // a number's value decides how far to jump to the next one
+    let jumps: Vec<usize> = [1, 3, 0, 0, 2, 0, 1, 5].into_iter().step_by_fn(|&n| n.max(1)).collect();
+    println!("jumps: {:?}", jumps);
+
+    // running total of the first rolls that reach 20
+    let totals: Vec<u32> = [6, 3, 5, 1, 6, 4, 2].into_iter().scan_until(0, |total, roll| total + roll, |&total| total >= 20).collect();
+    println!("totals: {:?}", totals);
+
+    for (initial, batch) in ["apple", "avocado", "banana", "blueberry", "cherry", "apricot"].into_iter().batch_by_key(|w| w.chars().next()) {
+        println!("{:?}: {:?}", initial, batch);
+    }
+}
+
+// Reserving `size` items up front would abort on a huge size, even when the iterator only has a handful of items; the lower bound of size_hint is what we know is coming
+fn capacity_for(size: usize, iter: &impl Iterator) -> usize {
+    size.min(iter.size_hint().0.max(1))
+}
+
+pub trait IteratorExt: Iterator + Sized {
+    // Vecs of `size` items, the last one shorter if the items don't divide evenly. Panics if size is 0, like slice::chunks.
+    fn chunks(self, size: usize) -> Chunks<Self> {
+        assert!(size > 0, "chunk size must be at least 1");
+        Chunks { iter: self, size }
+    }
+
+    // every run of `size` consecutive items, overlapping; fewer than `size` items give no windows at all
+    fn windows(self, size: usize) -> Windows<Self>
+    where
+        Self::Item: Clone,
+    {
+        assert!(size > 0, "window size must be at least 1");
+        let window = VecDeque::with_capacity(capacity_for(size, &self));
+        Windows {
+            iter: self,
+            size,
+            window,
+        }
+    }
+
+    // one item from self, one from other, and so on; once one of them runs out, the rest of the other follows
+    fn interleave<J>(self, other: J) -> Interleave<Self, J::IntoIter>
+    where
+        J: IntoIterator<Item = Self::Item>,
+    {
+        Interleave {
+            a: self,
+            b: other.into_iter(),
+            take_from_a: true,
+        }
+    }
+
+    // leaves out items that are equal to the one before them, like Vec::dedup
+    fn dedup(self) -> Dedup<Self>
+    where
+        Self::Item: PartialEq,
+    {
+        Dedup { iter: self, pending: None }
+    }
+
+    // Like step_by, but the step is worked out from each item that is returned: a step of 1 goes to the very next item, 2 skips one, and so on.
+    // Panics if the closure returns 0.
+    fn step_by_fn<F>(self, step: F) -> StepByFn<Self, F>
+    where
+        F: FnMut(&Self::Item) -> usize,
+    {
+        StepByFn { iter: self, step, skip: 0 }
+    }
+
+    // Every state of a running calculation, like scan, stopping after the first state that is done, which is included.
+    fn scan_until<S, F, D>(self, initial: S, next: F, done: D) -> ScanUntil<Self, S, F, D>
+    where
+        S: Clone,
+        F: FnMut(&S, Self::Item) -> S,
+        D: FnMut(&S) -> bool,
+    {
+        ScanUntil {
+            iter: self,
+            state: Some(initial),
+            next,
+            done,
+        }
+    }
+
+    // Neighbouring items with the same key, together with that key. Items with the same key that aren't next to each other end up in different batches, like slice::chunk_by.
+    fn batch_by_key<K, F>(self, key: F) -> BatchByKey<Self, K, F>
+    where
+        K: PartialEq,
+        F: FnMut(&Self::Item) -> K,
+    {
+        BatchByKey {
+            iter: self,
+            key,
+            pending: None,
+        }
+    }
+}
+
+impl<I: Iterator> IteratorExt for I {}
+
+pub struct Chunks<I> {
+    iter: I,
+    size: usize,
+}
+
+impl<I: Iterator> Iterator for Chunks<I> {
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = Vec::with_capacity(capacity_for(self.size, &self.iter));
+        while chunk.len() < self.size {
+            match self.iter.next() {
+                Some(item) => chunk.push(item),
+                None => break,
+            }
+        }
+        (!chunk.is_empty()).then_some(chunk)
+    }
+}
+
+// The window is kept between calls, so each step reads only one new item. The items are cloned because every window hands out its own Vec.
+pub struct Windows<I: Iterator> {
+    iter: I,
+    size: usize,
+    window: VecDeque<I::Item>,
+}
+
+impl<I> Iterator for Windows<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.window.len() == self.size {
+            self.window.pop_front();
+        }
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+        Some(self.window.iter().cloned().collect())
+    }
+}
+
+pub struct Interleave<I, J> {
+    a: I,
+    b: J,
+    take_from_a: bool,
+}
+
+impl<I, J> Iterator for Interleave<I, J>
+where
+    I: Iterator,
+    J: Iterator<Item = I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let from_a = self.take_from_a;
+        self.take_from_a = !from_a;
+        if from_a {
+            self.a.next().or_else(|| self.b.next())
+        } else {
+            self.b.next().or_else(|| self.a.next())
+        }
+    }
+}
+
+// To know where a run of equal items ends, Dedup has to read the first item after it. That item is kept in `pending` for the next call, so nothing has to be cloned.
+pub struct Dedup<I: Iterator> {
+    iter: I,
+    pending: Option<I::Item>,
+}
+
+impl<I> Iterator for Dedup<I>
+where
+    I: Iterator,
+    I::Item: PartialEq,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = match self.pending.take() {
+            Some(item) => item,
+            None => self.iter.next()?,
+        };
+        for item in self.iter.by_ref() {
+            if item != current {
+                self.pending = Some(item);
+                break;
+            }
+        }
+        Some(current)
+    }
+}
+
+pub struct StepByFn<I, F> {
+    iter: I,
+    step: F,
+    // items to skip before the next one is returned
+    skip: usize,
+}
+
+impl<I, F> Iterator for StepByFn<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> usize,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for _ in 0..self.skip {
+            self.iter.next()?;
+        }
+        let item = self.iter.next()?;
+        let step = (self.step)(&item);
+        assert!(step > 0, "step_by_fn: a step must be at least 1");
+        self.skip = step - 1;
+        Some(item)
+    }
+}
+
+pub struct ScanUntil<I, S, F, D> {
+    iter: I,
+    // None once a state was done
+    state: Option<S>,
+    next: F,
+    done: D,
+}
+
+impl<I, S, F, D> Iterator for ScanUntil<I, S, F, D>
+where
+    I: Iterator,
+    S: Clone,
+    F: FnMut(&S, I::Item) -> S,
+    D: FnMut(&S) -> bool,
+{
+    type Item = S;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = self.state.take()?;
+        let item = self.iter.next()?;
+        let state = (self.next)(&state, item);
+        if !(self.done)(&state) {
+            self.state = Some(state.clone());
+        }
+        Some(state)
+    }
+}
+
+// Like Dedup, the first item of the next batch has been read already when a batch ends; it waits in `pending` along with its key.
+pub struct BatchByKey<I: Iterator, K, F> {
+    iter: I,
+    key: F,
+    pending: Option<(K, I::Item)>,
+}
+
+impl<I, K, F> Iterator for BatchByKey<I, K, F>
+where
+    I: Iterator,
+    K: PartialEq,
+    F: FnMut(&I::Item) -> K,
+{
+    type Item = (K, Vec<I::Item>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, first) = match self.pending.take() {
+            Some(pending) => pending,
+            None => {
+                let item = self.iter.next()?;
+                ((self.key)(&item), item)
+            }
+        };
+        let mut batch = vec![first];
+        for item in self.iter.by_ref() {
+            let next_key = (self.key)(&item);
+            if next_key != key {
+                self.pending = Some((next_key, item));
+                break;
+            }
+            batch.push(item);
+        }
+        Some((key, batch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShiftRng;
+
+    // random lists of small numbers, so there are plenty of repeats and runs; lengths 0 to 29
+    fn random_lists() -> Vec<Vec<u64>> {
+        let mut rng = XorShiftRng::seed_from(50);
+        (0..200)
+            .map(|_| {
+                let len = rng.next_u64() % 30;
+                (0..len).map(|_| rng.next_u64() % 4).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chunks_and_windows_match_slices() {
+        for list in random_lists() {
+            for size in 1..6 {
+                let chunks: Vec<Vec<u64>> = list.iter().copied().chunks(size).collect();
+                assert_eq!(chunks, list.chunks(size).map(<[u64]>::to_vec).collect::<Vec<_>>());
+
+                let windows: Vec<Vec<u64>> = list.iter().copied().windows(size).collect();
+                assert_eq!(windows, list.windows(size).map(<[u64]>::to_vec).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn interleave_matches_zip_and_chain() {
+        let lists = random_lists();
+        for pair in lists.chunks(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            let shorter = a.len().min(b.len());
+            let expected: Vec<u64> = a
+                .iter()
+                .zip(b)
+                .flat_map(|(x, y)| [*x, *y])
+                .chain(a[shorter..].iter().copied())
+                .chain(b[shorter..].iter().copied())
+                .collect();
+            assert_eq!(a.iter().copied().interleave(b.iter().copied()).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn dedup_matches_vec_dedup() {
+        for list in random_lists() {
+            let mut expected = list.clone();
+            expected.dedup();
+            assert_eq!(list.into_iter().dedup().collect::<Vec<_>>(), expected);
+        }
+        // no Clone needed
+        let words = vec![String::from("a"), String::from("a"), String::from("b")];
+        assert_eq!(words.into_iter().dedup().count(), 2);
+    }
+
+    #[test]
+    fn step_by_fn_matches_step_by_for_a_fixed_step() {
+        for list in random_lists() {
+            for step in 1..5 {
+                let stepped: Vec<u64> = list.iter().copied().step_by_fn(|_| step).collect();
+                assert_eq!(stepped, list.iter().copied().step_by(step).collect::<Vec<_>>());
+            }
+            // and with a step that depends on the item, against the positions it should land on
+            let mut expected = Vec::new();
+            let mut i = 0;
+            while i < list.len() {
+                expected.push(list[i]);
+                i += list[i] as usize + 1;
+            }
+            assert_eq!(list.iter().copied().step_by_fn(|&n| n as usize + 1).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn scan_until_matches_scan_cut_after_the_first_done_state() {
+        for list in random_lists() {
+            let mut states: Vec<u64> = list
+                .iter()
+                .scan(0, |total, n| {
+                    *total += n;
+                    Some(*total)
+                })
+                .collect();
+            if let Some(done) = states.iter().position(|&total| total >= 10) {
+                states.truncate(done + 1);
+            }
+            let totals: Vec<u64> = list.iter().scan_until(0, |total, n| total + n, |&total| total >= 10).collect();
+            assert_eq!(totals, states);
+        }
+    }
+
+    #[test]
+    fn batch_by_key_matches_chunk_by() {
+        for list in random_lists() {
+            let expected: Vec<(bool, Vec<u64>)> = list
+                .chunk_by(|a, b| a % 2 == b % 2)
+                .map(|batch| (batch[0] % 2 == 0, batch.to_vec()))
+                .collect();
+            let batches: Vec<(bool, Vec<u64>)> = list.into_iter().batch_by_key(|n| n % 2 == 0).collect();
+            assert_eq!(batches, expected);
+        }
+    }
+
+    #[test]
+    fn adapters_chain() {
+        let result: Vec<u32> = (1..=20)
+            .step_by_fn(|_| 2)
+            .interleave([0, 0, 0])
+            .dedup()
+            .chunks(4)
+            .map(|chunk| chunk.iter().sum::<u32>())
+            .windows(2)
+            .map(|w| w[0].max(w[1]))
+            .collect();
+        // 1 0 3 0 | 5 0 7 9 | 11 13 15 17 | 19
+        assert_eq!(result, [21, 56, 56]);
+    }
+
+    #[test]
+    fn huge_sizes_on_short_iterators() {
+        assert_eq!((1..=3).chunks(usize::MAX).collect::<Vec<_>>(), [vec![1, 2, 3]]);
+        assert_eq!((1..=3).filter(|n| n % 2 == 1).chunks(usize::MAX).collect::<Vec<_>>(), [vec![1, 3]]);
+        assert_eq!((1..=3).windows(usize::MAX).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be at least 1")]
+    fn chunks_of_zero_panic() {
+        let _ = (0..3).chunks(0);
+    }
+}
@@ -0,0 +1,387 @@
+// Memoization
+// generate_workout in closures.rs calls its expensive closure twice with the same intensity, and sleeps two seconds both times. A Cacher wraps such a closure, calls it once per argument, and hands out the remembered result after that.
+// To keep memory bounded it holds at most `capacity` results and forgets the least recently used one first (LRU). Results can also expire after a time-to-live, measured with a Clock so tests don't have to wait.
+// SyncCacher is the same cache behind a Mutex, so several threads can share it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+
+pub fn memoization() {
+    let mut slow_square = Cacher::new(
+        |n: u64| {
+            thread::sleep(Duration::from_millis(50));
+            n * n
+        },
+        2,
+    )
+    .with_ttl(Duration::from_secs(60), SystemClock);
+    let start = Instant::now();
+    let results: Vec<u64> = [3, 3, 4, 3, 5, 4].into_iter().map(|n| slow_square.value(n)).collect();
+    println!("{:?} in {:?}, {:?}", results, start.elapsed(), slow_square.stats());
+
+    let shared = Arc::new(SyncCacher::new(|n: u64| (1..=n).product::<u64>(), 32).with_ttl(Duration::from_secs(60), SystemClock));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || (1..=20).map(|n| shared.value(n)).max())
+        })
+        .collect();
+    for handle in handles {
+        println!("largest factorial: {:?}", handle.join().unwrap_or_default());
+    }
+    let stats = shared.stats();
+    println!("shared cache: {:?}, hit rate {:.0}%", stats, stats.hit_rate().unwrap_or(0.0) * 100.0);
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    // results dropped to make room for new ones
+    pub evictions: u64,
+    // results dropped because they were older than the time-to-live
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    stored_at: Instant,
+    last_used: u64,
+}
+
+// The storage shared by Cacher and SyncCacher.
+// Every lookup gets the next number from `tick`, and `recency` maps those numbers back to keys, so its first entry is always the least recently used key. That makes each lookup and eviction O(log n).
+struct Cache<K, V, C> {
+    entries: HashMap<K, Entry<V>>,
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+    capacity: usize,
+    ttl: Option<Duration>,
+    clock: C,
+    stats: CacheStats,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone, C: Clock> Cache<K, V, C> {
+    fn new(capacity: usize, ttl: Option<Duration>, clock: C) -> Cache<K, V, C> {
+        Cache {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            capacity,
+            ttl,
+            clock,
+            stats: CacheStats::default(),
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let expired = match (self.entries.get(key), self.ttl) {
+            (None, _) => {
+                self.stats.misses += 1;
+                return None;
+            }
+            (Some(entry), Some(ttl)) => self.clock.now().duration_since(entry.stored_at) >= ttl,
+            (Some(_), None) => false,
+        };
+        if expired {
+            if let Some(entry) = self.entries.remove(key) {
+                self.recency.remove(&entry.last_used);
+            }
+            self.stats.expirations += 1;
+            self.stats.misses += 1;
+            return None;
+        }
+
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(key)?;
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(tick, key.clone());
+        entry.last_used = tick;
+        self.stats.hits += 1;
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(old) = self.entries.remove(&key) {
+            self.recency.remove(&old.last_used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+        }
+        let tick = self.next_tick();
+        self.recency.insert(tick, key.clone());
+        let stored_at = self.clock.now();
+        self.entries.insert(key, Entry { value, stored_at, last_used: tick });
+    }
+}
+
+// The book's Cacher, grown up: generic over the closure F, its argument K and its result V, with one remembered result per argument instead of a single value.
+// C is the clock used for the time-to-live; it defaults to the system clock.
+pub struct Cacher<F, K, V, C = SystemClock>
+where
+    F: Fn(K) -> V,
+{
+    calculation: F,
+    cache: Cache<K, V, C>,
+}
+
+impl<F, K, V> Cacher<F, K, V>
+where
+    F: Fn(K) -> V,
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new(calculation: F, capacity: usize) -> Cacher<F, K, V> {
+        Cacher {
+            calculation,
+            cache: Cache::new(capacity, None, SystemClock),
+        }
+    }
+
+    // forget results once they are older than ttl, as measured by clock
+    pub fn with_ttl<C: Clock>(self, ttl: Duration, clock: C) -> Cacher<F, K, V, C> {
+        Cacher {
+            calculation: self.calculation,
+            cache: Cache::new(self.cache.capacity, Some(ttl), clock),
+        }
+    }
+}
+
+impl<F, K, V, C> Cacher<F, K, V, C>
+where
+    F: Fn(K) -> V,
+    K: Eq + Hash + Clone,
+    V: Clone,
+    C: Clock,
+{
+    // the remembered result for arg, or the closure's result, which is then remembered
+    pub fn value(&mut self, arg: K) -> V {
+        if let Some(value) = self.cache.get(&arg) {
+            return value;
+        }
+        let value = (self.calculation)(arg.clone());
+        self.cache.insert(arg, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.cache.stats
+    }
+}
+
+// A Cacher that can be shared between threads (for example in an Arc) and used through &self.
+// The lock is only held while looking up and storing results, not while the closure runs, so a slow calculation for one key doesn't block lookups of other keys. Two threads that miss on the same key at the same time may both calculate it; the second result simply replaces the first.
+pub struct SyncCacher<F, K, V, C = SystemClock>
+where
+    F: Fn(K) -> V,
+{
+    calculation: F,
+    cache: Mutex<Cache<K, V, C>>,
+}
+
+impl<F, K, V> SyncCacher<F, K, V>
+where
+    F: Fn(K) -> V,
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new(calculation: F, capacity: usize) -> SyncCacher<F, K, V> {
+        SyncCacher {
+            calculation,
+            cache: Mutex::new(Cache::new(capacity, None, SystemClock)),
+        }
+    }
+
+    // like Cacher::with_ttl; to share the cacher between threads the clock has to be Send as well
+    pub fn with_ttl<C: Clock>(self, ttl: Duration, clock: C) -> SyncCacher<F, K, V, C> {
+        let capacity = self.cache.into_inner().unwrap_or_else(|e| e.into_inner()).capacity;
+        SyncCacher {
+            calculation: self.calculation,
+            cache: Mutex::new(Cache::new(capacity, Some(ttl), clock)),
+        }
+    }
+}
+
+impl<F, K, V, C> SyncCacher<F, K, V, C>
+where
+    F: Fn(K) -> V,
+    K: Eq + Hash + Clone,
+    V: Clone,
+    C: Clock,
+{
+    // A panic in another thread while it held the lock can't leave the cache half-updated in a way that matters here, so a poisoned lock is used anyway
+    pub fn value(&self, arg: K) -> V {
+        let cached = self.cache.lock().unwrap_or_else(|e| e.into_inner()).get(&arg);
+        if let Some(value) = cached {
+            return value;
+        }
+        let value = (self.calculation)(arg.clone());
+        self.cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(arg, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn calls_the_closure_once_per_argument() {
+        let calls = Cell::new(0);
+        let mut square = Cacher::new(
+            |n: u64| {
+                calls.set(calls.get() + 1);
+                n * n
+            },
+            10,
+        );
+        assert_eq!(square.value(4), 16);
+        assert_eq!(square.value(4), 16);
+        assert_eq!(square.value(5), 25);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(square.stats(), CacheStats { hits: 1, misses: 2, evictions: 0, expirations: 0 });
+        assert_eq!(square.stats().hit_rate(), Some(1.0 / 3.0));
+
+        // any Clone result and Hash key works, not just numbers
+        let mut greeting = Cacher::new(|name: String| format!("Hello, {}!", name), 2);
+        assert_eq!(greeting.value(String::from("Ferris")), "Hello, Ferris!");
+        assert_eq!(CacheStats::default().hit_rate(), None);
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_result() {
+        let calls = Cell::new(0);
+        let mut double = Cacher::new(
+            |n: i32| {
+                calls.set(calls.get() + 1);
+                n * 2
+            },
+            2,
+        );
+        double.value(1);
+        double.value(2);
+        // using 1 again makes 2 the least recently used
+        double.value(1);
+        double.value(3);
+        assert_eq!(double.stats().evictions, 1);
+
+        calls.set(0);
+        double.value(1);
+        double.value(3);
+        assert_eq!(calls.get(), 0);
+        double.value(2);
+        assert_eq!(calls.get(), 1);
+
+        let mut uncached = Cacher::new(|n: i32| n, 0);
+        uncached.value(1);
+        uncached.value(1);
+        assert_eq!(uncached.stats().misses, 2);
+    }
+
+    #[test]
+    fn results_expire_after_the_ttl() {
+        let clock = ManualClock::new();
+        let calls = Cell::new(0);
+        let mut cached = Cacher::new(
+            |n: i32| {
+                calls.set(calls.get() + 1);
+                n
+            },
+            10,
+        )
+        .with_ttl(Duration::from_secs(60), &clock);
+
+        cached.value(7);
+        clock.advance(Duration::from_secs(59));
+        cached.value(7);
+        assert_eq!(calls.get(), 1);
+        clock.advance(Duration::from_secs(1));
+        cached.value(7);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(cached.stats(), CacheStats { hits: 1, misses: 2, evictions: 0, expirations: 1 });
+    }
+
+    #[test]
+    fn sync_results_expire_after_the_ttl() {
+        let clock = ManualClock::new();
+        let calls = AtomicUsize::new(0);
+        let cached = SyncCacher::new(
+            |n: i32| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                n
+            },
+            10,
+        )
+        .with_ttl(Duration::from_secs(60), &clock);
+
+        cached.value(7);
+        clock.advance(Duration::from_secs(59));
+        cached.value(7);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        clock.advance(Duration::from_secs(1));
+        cached.value(7);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(cached.stats(), CacheStats { hits: 1, misses: 2, evictions: 0, expirations: 1 });
+    }
+
+    #[test]
+    fn can_be_shared_between_threads() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&calls);
+        let cacher = Arc::new(SyncCacher::new(
+            move |n: u64| {
+                counted.fetch_add(1, Ordering::SeqCst);
+                (1..=n).product::<u64>()
+            },
+            100,
+        ));
+
+        // warm the cache first, so every thread below hits it
+        for n in 0..10 {
+            cacher.value(n);
+        }
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let cacher = Arc::clone(&cacher);
+                thread::spawn(move || (0..10).map(|n| cacher.value(n)).sum::<u64>())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 409114);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 10);
+        assert_eq!(cacher.stats().hits, 40);
+    }
+}
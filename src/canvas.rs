@@ -0,0 +1,122 @@
+// A character canvas
+// The Draw implementations in oop.rs only printed "Draw in Button". To see what a screen actually looks like they draw into a Canvas instead: a grid of width x height characters that starts out blank, and is printed (or compared in a test) as text once everything is drawn.
+// Drawing outside the canvas is silently clipped, so a component that is too big for its area never panics.
+
+use std::fmt;
+
+// An area of the canvas, in characters, with (x, y) the top-left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    // the area inside a one character border
+    pub fn inner(&self) -> Rect {
+        Rect {
+            x: self.x.saturating_add(1),
+            y: self.y.saturating_add(1),
+            width: self.width.saturating_sub(2),
+            height: self.height.saturating_sub(2),
+        }
+    }
+}
+
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    cells: Vec<char>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Canvas {
+        Canvas {
+            width,
+            height,
+            cells: vec![' '; width as usize * height as usize],
+        }
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, c: char) {
+        if x < self.width && y < self.height {
+            self.cells[y as usize * self.width as usize + x as usize] = c;
+        }
+    }
+}
+
+// What a component can draw: borders and text, positioned in character cells.
+// Draw implementations only talk to this trait, so the same component can be drawn into a Canvas for the terminal or turned into SVG (see svg.rs).
+pub trait Renderer {
+    fn border(&mut self, area: Rect);
+    // text from (x, y) to the right, cut off after max_width characters
+    fn text(&mut self, x: u32, y: u32, text: &str, max_width: u32);
+}
+
+impl Renderer for Canvas {
+    fn text(&mut self, x: u32, y: u32, text: &str, max_width: u32) {
+        for (i, c) in text.chars().take(max_width as usize).enumerate() {
+            self.set(x.saturating_add(i as u32), y, c);
+        }
+    }
+
+    // +---+
+    // |   |
+    // +---+
+    fn border(&mut self, area: Rect) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let (right, bottom) = (area.x.saturating_add(area.width - 1), area.y.saturating_add(area.height - 1));
+        // only the part of each line that is on the canvas is walked, so a huge area doesn't take forever to clip
+        for x in area.x..=right.min(self.width) {
+            self.set(x, area.y, '-');
+            self.set(x, bottom, '-');
+        }
+        for y in area.y..=bottom.min(self.height) {
+            self.set(area.x, y, '|');
+            self.set(right, y, '|');
+        }
+        for (x, y) in [(area.x, area.y), (right, area.y), (area.x, bottom), (right, bottom)] {
+            self.set(x, y, '+');
+        }
+    }
+}
+
+// One line per row, with trailing spaces trimmed so snapshots in tests don't depend on invisible whitespace
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.cells.chunks(self.width.max(1) as usize).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let line: String = row.iter().collect();
+            write!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_borders_and_clips_text() {
+        let mut canvas = Canvas::new(8, 4);
+        canvas.border(Rect::new(1, 0, 6, 3));
+        canvas.text(2, 1, "hello world", 4);
+        canvas.text(6, 3, "clipped", 10);
+        canvas.set(100, 100, '!');
+
+        assert_eq!(canvas.to_string(), " +----+\n |hell|\n +----+\n      cl");
+        assert_eq!(Rect::new(1, 0, 6, 3).inner(), Rect::new(2, 1, 4, 1));
+        assert_eq!(Rect::new(0, 0, 1, 1).inner().width, 0);
+    }
+}
@@ -0,0 +1,33 @@
+// Command line interface
+// Running the program without arguments walks through every lesson. Given arguments, it runs a single command instead and exits:
+//     cargo run -- transform pig-latin "first apple"
+//     echo "Hello World" | cargo run -- transform snake
+//     cargo run -- workout plan 40
+
+use std::process::ExitCode;
+
+use crate::text_transform;
+use crate::workout;
+
+const USAGE: &str = "usage: lesson2 [transform <name> [text...] | workout <plan|log|history> ...]";
+
+pub fn run(args: &[String]) -> ExitCode {
+    let result = match args.split_first() {
+        Some((command, rest)) if command == "transform" => text_transform::run_command(rest),
+        Some((command, rest)) if command == "workout" => workout::run_command(rest),
+        Some((command, _)) => Err(format!("unknown command {:?}\n{}", command, USAGE)),
+        None => Err(String::from(USAGE)),
+    };
+
+    // 2 is the conventional exit status for a command used incorrectly
+    match result {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
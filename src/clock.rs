@@ -0,0 +1,70 @@
+// Time as a dependency
+// Code that calls Instant::now() or thread::sleep directly is hard to test: the test has to really wait, and the results depend on how fast the machine is.
+// Passing a Clock in instead lets the real program use the system clock while tests use a ManualClock that only moves when the test tells it to.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+// lets a type that stores its clock borrow one instead of owning it, so a test can keep moving a ManualClock it has lent out
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration);
+    }
+}
+
+// A clock for tests. Sleeping returns immediately and just moves the clock forward, and every sleep is recorded so tests can check the delays.
+// Cell and RefCell give us interior mutability, so the clock can be shared by & reference like the real one.
+#[cfg(test)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: std::cell::Cell<Duration>,
+    pub sleeps: std::cell::RefCell<Vec<Duration>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock {
+            start: Instant::now(),
+            elapsed: std::cell::Cell::new(Duration::ZERO),
+            sleeps: std::cell::RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.set(self.elapsed.get() + duration);
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.sleeps.borrow_mut().push(duration);
+        self.advance(duration);
+    }
+}
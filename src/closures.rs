@@ -10,7 +10,7 @@ pub fn closures(){
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
-enum SHirtColor{
+pub enum SHirtColor{
     Red,
     Blue
 }
@@ -47,6 +47,7 @@ impl Inventory{
     }
 }
 
+// shirt_inventory.rs grows this into an inventory with sizes, real stock, restocking and tie-breaking closures
 fn giveaway_shirt(){
     let store = Inventory{
         shirts: vec![SHirtColor::Blue, SHirtColor::Red, SHirtColor::Blue],
@@ -76,16 +77,19 @@ Closures, on the other hand, aren’t used in an exposed interface like this: th
 //example of closure
 use std::thread;
 use std::time::Duration;
+use crate::cacher::Cacher;
+use crate::comparator::{by_key, by_optional_key, Nulls};
 fn generate_workout(intensity: i32, random_number: i32){
     //cliosure stored in a variable
-    let expensive_closure = |num: i32| -> i32{
+    // wrapped in a Cacher (see cacher.rs), so the second call with the same intensity returns the remembered result instead of sleeping again
+    let mut expensive_closure = Cacher::new(|num: i32| -> i32{
         println!("calculating slowly...");
         thread::sleep(Duration::from_secs(2));
         num
-    };
+    }, 1);
     if intensity <25{
-        println!("Today, do {} pushups!", expensive_closure(intensity));
-        println!("Next, do {} pushups!", expensive_closure(intensity));
+        println!("Today, do {} pushups!", expensive_closure.value(intensity));
+        println!("Next, do {} pushups!", expensive_closure.value(intensity));
     } else{
         if random_number == 3{
             println!("Take a break today! Remember to stay hydrated!");
@@ -93,7 +97,7 @@ fn generate_workout(intensity: i32, random_number: i32){
         else {
             println!(
                 "Today, run for {} minutes!",
-                expensive_closure(intensity)
+                expensive_closure.value(intensity)
             );
         }
     }
@@ -127,6 +131,7 @@ FnOnce applies to closures that can be called once. All closures implement at le
 FnMut applies to closures that don’t move captured values out of their body, but that might mutate the captured values. These closures can be called more than once.
 Fn applies to closures that don’t move captured values out of their body and that don’t mutate captured values, as well as closures that capture nothing from their environment. These closures can be called more than once without mutating their environment, which is important in cases such as calling a closure multiple times concurrently.
 */
+// event_bus.rs has handlers of all three kinds
 #[derive(Debug)]
 
 struct Rectangle{
@@ -141,5 +146,25 @@ fn closure_traits(){
         Rectangle { width: 7, height: 12 },
     ];
     list.sort_by_key(|r| r.width);
-    println!("{:#?}", list)
+    println!("{:#?}", list);
+
+    // sort_by_key takes a single key; comparator.rs combines several, and can reverse them
+    let mut list = [
+        Rectangle { width: 3, height: 4 },
+        Rectangle { width: 6, height: 2 },
+        Rectangle { width: 2, height: 6 },
+        Rectangle { width: 5, height: 0 },
+    ];
+    by_key(|r: &Rectangle| r.width * r.height).reversed().then_by(|r| r.width).sort(&mut list);
+    println!("largest area first: {:?}", list);
+
+    // a rectangle without height has no width to height ratio (here in percent); put it last
+    let ratio = |r: &Rectangle| (r.width * 100).checked_div(r.height);
+    by_optional_key(ratio, Nulls::Last).sort_unstable(&mut list);
+    println!("narrowest first: {:?}", list);
+    // reversed() only reverses the ratios, so the rectangle without one stays last
+    by_optional_key(ratio, Nulls::Last).reversed().sort_unstable(&mut list);
+    println!("widest first: {:?}", list);
+    by_optional_key(ratio, Nulls::First).reversed().sort_unstable(&mut list);
+    println!("no ratio, then widest: {:?}", list)
 }
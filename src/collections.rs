@@ -3,6 +3,9 @@
 // Rust conveniently provides the vec! macro, which will create a new vector that holds the values you give it.
 
 use core::hash;
+use crate::string_building;
+use crate::text_transform::Transform;
+use crate::spreadsheet::Formula;
 use std::vec;
 
 pub fn collections() {
@@ -12,6 +15,7 @@ pub fn collections() {
     iterate_vectors();
     enum_store_multiple_values();
     strings();
+    transforming_strings();
     hash_maps();
 }
 
@@ -70,11 +74,13 @@ fn iterate_vectors() {
     }
 }
 
+// spreadsheet.rs uses this enum for the cells of a whole sheet, where a cell can also hold a formula like =A1+B2*3
 #[derive(Debug)]
-enum SpreadSheetCell{
+pub enum SpreadSheetCell{
     Int(i32),
     FLoat(f64),
     Text(String),
+    Formula(Formula),
 }
 fn enum_store_multiple_values(){
     let row = vec![
@@ -82,6 +88,10 @@ fn enum_store_multiple_values(){
         SpreadSheetCell::Text(String::from("blue")),
         SpreadSheetCell::FLoat(10.47),
     ];
+    let row: Vec<SpreadSheetCell> = row
+        .into_iter()
+        .chain(Formula::parse("=A1*2").map(SpreadSheetCell::Formula))
+        .collect();
 
     println!("Enum with vector {:?}", row);
 }
@@ -114,6 +124,9 @@ fn strings(){
         let s3 = String::from("toe");
 
         let s2 = format!("{st}-{s2}-{s3}"); //format! is much easier to read, and the code generated by the format! macro uses references so that this call doesn’t take ownership of any of its parameters.
+        // when the pieces come from an iterator instead, string_building::join does the same without knowing how many there are
+        let s4 = string_building::join(["tic", "tac", "toe"], "-");
+        println!("{s4}");
 
         for c in s2.chars() {
             println!("{c}")
@@ -121,6 +134,14 @@ fn strings(){
 }
 
 
+// Once we can walk a string word by word and char by char, we can transform it. The transformations live in text_transform.rs and can also be run from the command line.
+fn transforming_strings(){
+    let sentence = "the quick brown fox jumps over the lazy dog";
+    for transform in Transform::ALL {
+        println!("{}: {}", transform, transform.apply(sentence));
+    }
+}
+
 use std::collections::HashMap;
 fn hash_maps(){
     // The type HashMap<K, V> stores a mapping of keys of type K to values of type V using a hashing function, which determines how it places these keys and values into memory. 
@@ -0,0 +1,186 @@
+// Comparators
+// closure_traits in closures.rs sorts rectangles with sort_by_key, which takes one key and always sorts ascending. Sorting by width and then by height, or largest first, means writing the comparison closure by hand.
+// A Comparator is such a comparison closure, built from small pieces: by_key(|r| r.width).then_by(|r| r.height).reversed() sorts by width, then height, both descending.
+// Keys that are an Option can put the missing values first or last, and every comparator can sort stably or unstably.
+
+use std::cmp::Ordering;
+
+// The bool says whether the keys are compared in reverse. Passing it down, instead of swapping a and b, lets by_optional_key reverse its keys while keeping the missing ones where they were asked to go.
+type Compare<T> = Box<dyn Fn(&T, &T, bool) -> Ordering>;
+
+pub struct Comparator<T> {
+    compare: Compare<T>,
+}
+
+// where by_optional_key puts items that have no key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
+// Any comparison closure, for orderings that can't be written as a key, like comparing borrowed strings without cloning them
+pub fn by<T: 'static>(compare: impl Fn(&T, &T) -> Ordering + 'static) -> Comparator<T> {
+    Comparator {
+        compare: Box::new(move |a, b, reversed| if reversed { compare(b, a) } else { compare(a, b) }),
+    }
+}
+
+// Like sort_by_key: compare the keys. The key is computed again for every comparison, so it should be cheap, like a number or a copied field.
+pub fn by_key<T: 'static, K: Ord>(key: impl Fn(&T) -> K + 'static) -> Comparator<T> {
+    by(move |a, b| key(a).cmp(&key(b)))
+}
+
+// Option already implements Ord with None first; this makes the choice explicit, and makes Last possible.
+// reversed() only reverses the keys that are there: with Nulls::Last the missing keys stay at the end in a descending order too.
+pub fn by_optional_key<T: 'static, K: Ord>(key: impl Fn(&T) -> Option<K> + 'static, nulls: Nulls) -> Comparator<T> {
+    let missing = match nulls {
+        Nulls::First => Ordering::Less,
+        Nulls::Last => Ordering::Greater,
+    };
+    Comparator {
+        compare: Box::new(move |a, b, reversed| match (key(a), key(b)) {
+            (Some(a), Some(b)) if reversed => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => missing,
+            (Some(_), None) => missing.reverse(),
+        }),
+    }
+}
+
+impl<T: 'static> Comparator<T> {
+    pub fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.compare)(a, b, false)
+    }
+
+    // only asks next when this comparator finds the two equal
+    pub fn then(self, next: Comparator<T>) -> Comparator<T> {
+        Comparator {
+            compare: Box::new(move |a, b, reversed| {
+                (self.compare)(a, b, reversed).then_with(|| (next.compare)(a, b, reversed))
+            }),
+        }
+    }
+
+    pub fn then_by<K: Ord>(self, key: impl Fn(&T) -> K + 'static) -> Comparator<T> {
+        self.then(by_key(key))
+    }
+
+    // the opposite order, for every key compared so far; missing keys from by_optional_key stay first or last
+    pub fn reversed(self) -> Comparator<T> {
+        Comparator {
+            compare: Box::new(move |a, b, reversed| (self.compare)(a, b, !reversed)),
+        }
+    }
+
+    // Items that compare equal keep the order they were in
+    pub fn sort(&self, items: &mut [T]) {
+        items.sort_by(|a, b| self.compare(a, b));
+    }
+
+    // Faster and doesn't allocate, but items that compare equal may end up in any order
+    pub fn sort_unstable(&self, items: &mut [T]) {
+        items.sort_unstable_by(|a, b| self.compare(a, b));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShiftRng;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Runner {
+        name: &'static str,
+        club: &'static str,
+        // None if they didn't finish
+        minutes: Option<u32>,
+    }
+
+    fn runners() -> Vec<Runner> {
+        [
+            ("Ann", "north", Some(42)),
+            ("Bob", "south", None),
+            ("Cid", "north", Some(38)),
+            ("Dee", "south", Some(42)),
+            ("Eve", "north", None),
+            ("Fay", "south", Some(38)),
+        ]
+        .into_iter()
+        .map(|(name, club, minutes)| Runner { name, club, minutes })
+        .collect()
+    }
+
+    fn names(runners: &[Runner]) -> Vec<&'static str> {
+        runners.iter().map(|r| r.name).collect()
+    }
+
+    #[test]
+    fn sorts_by_several_keys() {
+        let mut list = runners();
+        by_key(|r: &Runner| r.club)
+            .then_by(|r| r.minutes)
+            .sort(&mut list);
+        assert_eq!(names(&list), ["Eve", "Cid", "Ann", "Bob", "Fay", "Dee"]);
+
+        by_key(|r: &Runner| r.club).reversed().then(by(|a: &Runner, b: &Runner| a.name.cmp(b.name))).sort(&mut list);
+        assert_eq!(names(&list), ["Bob", "Dee", "Fay", "Ann", "Cid", "Eve"]);
+
+        by_key(|r: &Runner| r.club).then_by(|r| r.name).reversed().sort(&mut list);
+        assert_eq!(names(&list), ["Fay", "Dee", "Bob", "Eve", "Cid", "Ann"]);
+    }
+
+    #[test]
+    fn puts_missing_keys_first_or_last() {
+        let mut list = runners();
+        by_optional_key(|r: &Runner| r.minutes, Nulls::Last).sort(&mut list);
+        assert_eq!(names(&list), ["Cid", "Fay", "Ann", "Dee", "Bob", "Eve"]);
+
+        by_optional_key(|r: &Runner| r.minutes, Nulls::First).sort(&mut list);
+        assert_eq!(names(&list), ["Bob", "Eve", "Cid", "Fay", "Ann", "Dee"]);
+
+        // slowest finisher first, and still the runners who didn't finish at the end
+        by_optional_key(|r: &Runner| r.minutes, Nulls::Last)
+            .reversed()
+            .then_by(|r| r.name)
+            .sort(&mut list);
+        assert_eq!(names(&list), ["Ann", "Dee", "Cid", "Fay", "Bob", "Eve"]);
+
+        by_optional_key(|r: &Runner| r.minutes, Nulls::First)
+            .reversed()
+            .then_by(|r| r.name)
+            .sort(&mut list);
+        assert_eq!(names(&list), ["Bob", "Eve", "Ann", "Dee", "Cid", "Fay"]);
+
+        // reversing twice, or reversing a comparator that contains one, still leaves the missing keys alone
+        by_key(|r: &Runner| r.club)
+            .then(by_optional_key(|r: &Runner| r.minutes, Nulls::Last).reversed())
+            .reversed()
+            .sort(&mut list);
+        assert_eq!(names(&list), ["Fay", "Dee", "Bob", "Cid", "Ann", "Eve"]);
+    }
+
+    #[test]
+    fn stable_sorting_keeps_the_order_of_equal_items() {
+        let mut rng = XorShiftRng::seed_from(48);
+        for _ in 0..20 {
+            // (key, original position), with few enough keys that there are plenty of ties
+            let items: Vec<(u64, usize)> = (0..200).map(|i| (rng.next_u64() % 8, i)).collect();
+            let by_first = || by_key(|item: &(u64, usize)| item.0);
+
+            let mut stable = items.clone();
+            by_first().sort(&mut stable);
+            let mut expected = items.clone();
+            expected.sort();
+            assert_eq!(stable, expected);
+
+            // unstable sorting puts the keys in the same order, but may shuffle the items within a key
+            let mut unstable = items.clone();
+            by_first().sort_unstable(&mut unstable);
+            assert!(unstable.iter().map(|item| item.0).eq(expected.iter().map(|item| item.0)));
+            unstable.sort();
+            assert_eq!(unstable, expected);
+        }
+    }
+}
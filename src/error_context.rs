@@ -0,0 +1,120 @@
+// Adding context to errors
+// File::open(path)?.read_to_string(&mut username)? propagates a bare io::Error such as "No such file or directory". By the time it reaches the caller, nobody knows which step failed or which file it was about.
+// The Context trait adds a method to every Result whose error implements Error. Calling .context("...") before ? wraps the error in a ContextError that carries a human-readable message, keeps the original error as its source, and captures a backtrace.
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::error::Error;
+use std::fmt;
+
+use crate::error_handling::render_report;
+
+#[derive(Debug)]
+pub struct ContextError {
+    context: String,
+    source: Box<dyn Error + Send + Sync + 'static>,
+    backtrace: Backtrace,
+}
+
+impl ContextError {
+    fn new(context: String, source: Box<dyn Error + Send + Sync + 'static>) -> ContextError {
+        // Backtrace::capture only does the (slow) work when RUST_BACKTRACE or RUST_LIB_BACKTRACE is set.
+        // The innermost error already has the most useful backtrace, so outer layers don't capture another one.
+        let backtrace = if source.downcast_ref::<ContextError>().is_some() {
+            Backtrace::disabled()
+        } else {
+            Backtrace::capture()
+        };
+        ContextError {
+            context,
+            source,
+            backtrace,
+        }
+    }
+
+    fn captured_backtrace(&self) -> Option<&Backtrace> {
+        if self.backtrace.status() == BacktraceStatus::Captured {
+            return Some(&self.backtrace);
+        }
+        self.source
+            .downcast_ref::<ContextError>()
+            .and_then(|inner| inner.captured_backtrace())
+    }
+
+    // One line per layer of context followed by the original error, plus the backtrace when one was captured
+    pub fn report(&self) -> String {
+        let mut report = render_report(self);
+        if let Some(backtrace) = self.captured_backtrace() {
+            report.push_str(&format!("\n\nbacktrace:\n{}", backtrace));
+        }
+        report
+    }
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.context)
+    }
+}
+
+impl Error for ContextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+// An extension trait: we can't add methods to std's Result directly, but we can define our own trait and implement it for Result.
+// with_context takes a closure so the message is only built when there actually is an error.
+pub trait Context<T> {
+    fn context(self, context: impl Into<String>) -> Result<T, ContextError>;
+    fn with_context<F: FnOnce() -> String>(self, f: F) -> Result<T, ContextError>;
+}
+
+impl<T, E> Context<T> for Result<T, E>
+where
+    E: Error + Send + Sync + 'static,
+{
+    fn context(self, context: impl Into<String>) -> Result<T, ContextError> {
+        self.map_err(|error| ContextError::new(context.into(), Box::new(error)))
+    }
+
+    fn with_context<F: FnOnce() -> String>(self, f: F) -> Result<T, ContextError> {
+        self.map_err(|error| ContextError::new(f(), Box::new(error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{self, ErrorKind};
+
+    fn open_config() -> Result<(), ContextError> {
+        Err(io::Error::from(ErrorKind::NotFound)).context("opening config.toml")
+    }
+
+    fn start_app() -> Result<(), ContextError> {
+        open_config().with_context(|| format!("starting {}", "lesson2"))
+    }
+
+    #[test]
+    fn each_question_mark_adds_a_layer() {
+        let error = start_app().unwrap_err();
+        assert_eq!(error.to_string(), "starting lesson2");
+
+        let report = error.report();
+        let mut lines = report.lines();
+        assert_eq!(lines.next(), Some("error: starting lesson2"));
+        assert_eq!(lines.next(), Some("  caused by: opening config.toml"));
+        assert_eq!(lines.next(), Some("  caused by: entity not found"));
+
+        // a backtrace is only included when RUST_BACKTRACE asked for one
+        let captured = error.captured_backtrace().is_some();
+        assert_eq!(report.contains("backtrace:"), captured);
+        assert_eq!(error.backtrace.status(), BacktraceStatus::Disabled);
+    }
+
+    #[test]
+    fn ok_values_pass_through_untouched() {
+        let value: Result<i32, io::Error> = Ok(3);
+        assert_eq!(value.with_context(|| unreachable!()).unwrap(), 3);
+    }
+}
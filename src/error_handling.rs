@@ -4,68 +4,88 @@
 //Recoverable Errors with Result
 
 use std::error;
+use std::fmt;
 use std::fs::{read_to_string, File};
 use std::io::{self, ErrorKind, Read};
+use std::num::ParseIntError;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::clock::SystemClock;
+use crate::error_context::{Context, ContextError};
+use crate::file_service::{FileError, FileService, FileServiceConfig};
+use crate::retry::{is_transient, retry, Backoff, FlakyFileReader, RetryPolicy};
+use crate::validation::{Age, Email, Guess, ShoeSize, ValidationError};
 
 // The return type of File::open is a Result<T, E>.
 // Note that, like the Option enum, the Result enum and its variants have been brought into scope by the prelude, so we don’t need to specify Result:: before the Ok and Err variants in the match arms.'
 
 pub fn errors_handling_rust() {
-    recoverale_error();
-    matching_on_different_errors();
-    propagating_errors();
+    let path = Path::new("hello.txt");
+    if let Err(error) = recoverale_error(path) {
+        report(&error);
+    }
+    match matching_on_different_errors(path) {
+        Ok(_) => println!("{} is ready to be read", path.display()),
+        Err(error) => report(&error),
+    }
+    propagating_errors(path);
+    custom_error_types();
+    creating_custom_types_for_validation();
+    file_service_example(path);
+    retrying_transient_errors();
 }
 
-// there’s no file named hello.txt in our current directory and we run this code, we’ll see the following output from the panic!
-fn recoverale_error() {
-    let greeting_file_result = File::open("hello.txt");
+// there’s no file named hello.txt in our current directory and we run this code, we’d see the panic! from the book's version of this function.
+// Instead of panicking, the error goes back to the caller, which reports it and carries on.
+fn recoverale_error(path: &Path) -> Result<File, AppError> {
+    let greeting_file_result = File::open(path);
 
-    let greeting_file = match greeting_file_result {
-        Ok(file) => file,
-        Err(error) => panic!("Problem opening the file: {:?}", error),
-    };
+    match greeting_file_result {
+        Ok(file) => Ok(file),
+        Err(error) => Err(AppError::from(error)),
+    }
 }
 
-fn matching_on_different_errors() {
-    let greeting_file_result = File::open("hello.txt");
+fn matching_on_different_errors(path: &Path) -> Result<File, AppError> {
+    let greeting_file_result = File::open(path);
     let greeting_file = match greeting_file_result {
         Ok(file) => file,
         Err(error) => match error.kind() { 
-            ErrorKind::NotFound => match File::create("hello.txt") {
-                Ok(fc) => fc,
-                Err(e) => panic!("Problem creatingd the file: {:?}", e),
-            },
-            other_error => {
-                panic!("Problem opening the file: {:?}", other_error);
-            }
+            ErrorKind::NotFound => File::create(path)?,
+            _ => return Err(AppError::from(error)),
         },
 
     };
+    drop(greeting_file);
 
     // Using match works well enough, but it can be a bit verbose and doesn’t always communicate intent well. The Result<T, E> type has many helper methods defined on it to do various, more specific tasks. The unwrap method is a shortcut method implemented just like the match expression we wrote in Listing 9-4. If the Result value is the Ok variant, unwrap will return the value inside the Ok. If the Result is the Err variant, unwrap will call the panic! macro for us. 
-    let greeting = File::open("hello.txt").unwrap();
-    // or
-    let greeting_file = File::open("hello.txt")
-        .expect("hello.txt should be included in this project");
+    // unwrap and expect still panic, so here the same "open it, or create it when it is missing" goes through FileService, which returns a FileError instead
+    let service = FileService::new(FileServiceConfig::default());
+    Ok(service.open(path)?)
 }
 
 
-fn propagating_errors(){
-    let username = read_username_from_file();
+fn propagating_errors(path: &Path){
+    let username = read_username_from_file(path);
     println!("{:?}", username);
-    let username_two = read_username_from_file_shortcut();
+    let username_two = read_username_from_file_shortcut(path);
     println!("{:?}", username_two);
+    // context stacks up: the caller adds what it was doing on top of the callee's context
+    if let Err(error) = read_username_from_file_shortcut(Path::new("missing.txt")).context("loading the user profile") {
+        eprintln!("{}", error.report());
+    }
     let result_chars = last_char_of_first_line("Subscribe to my channel\n more content added");
     println!("{:?}", result_chars);
 }
 
 
-fn read_username_from_file()-> Result<String, io::Error>{
-    let username_file_result = File::open("Hello.txt");
+fn read_username_from_file(path: &Path)-> Result<String, AppError>{
+    let username_file_result = File::open(path);
 
     let mut username_file = match username_file_result {
         Ok(file) => file,
-        Err(error) => return Err(error),
+        Err(error) => return Err(AppError::from(error)),
     };
 
     // creates a new String in variable username and calls the read_to_string method on the file handle in username_file to read the contents of the file into username.
@@ -73,16 +93,21 @@ fn read_username_from_file()-> Result<String, io::Error>{
     // However, we don’t need to explicitly say return, because this is the last expression in the function.
     match username_file.read_to_string(&mut username) {
         Ok(_) => Ok(username),
-        Err(e) => Err(e),
+        Err(e) => Err(e.into()),
     }
 }
 
-fn read_username_from_file_shortcut()-> Result<String, io::Error>{
+// The ? operator calls From::from on the error it propagates, so errors can be converted on the way out (see AppError below).
+// A bare io::Error doesn't say which step failed or which file was involved, so each step attaches that with the Context trait from error_context.rs before ? propagates it.
+fn read_username_from_file_shortcut(path: &Path)-> Result<String, ContextError>{
      //or
 
      let mut username = String::new();
 
-     File::open("hello.txt")?.read_to_string(&mut username)?;
+     File::open(path)
+         .with_context(|| format!("could not open {}", path.display()))?
+         .read_to_string(&mut username)
+         .with_context(|| format!("could not read a username from {}", path.display()))?;
      Ok(username)
 }
 
@@ -91,6 +116,219 @@ fn last_char_of_first_line(text: &str) -> Option<char> {
 }
 
 
+// The types themselves live in validation.rs. Each one can only be created through a constructor that checks the value, so an invalid Email, Age, ShoeSize or Guess can never exist.
 fn creating_custom_types_for_validation(){
+    for input in ["50", "0", "101", "fifty"] {
+        match input.parse::<Guess>() {
+            Ok(guess) => println!("{} is a valid guess", guess.value()),
+            Err(error) => println!("invalid guess: {}", error),
+        }
+    }
+    // a number the program computed itself, rather than text the user typed, goes through Guess::new
+    let halfway = (1 + 100) / 2;
+    match Guess::new(halfway) {
+        Ok(guess) => println!("starting in the middle with {}", guess.value()),
+        Err(error) => println!("invalid guess: {}", error),
+    }
+
+    match Email::try_from("weldon@gmail.com") {
+        Ok(email) => println!("valid email: {}", email.as_str()),
+        Err(error) => println!("{}", error),
+    }
+    if let Err(error) = Email::try_from("weldon.gmail.com") {
+        println!("{}", error);
+    }
+
+    let age = Age::try_from("25").map(|age| age.value());
+    let size = ShoeSize::try_from("44").map(|size| size.value());
+    println!("age: {:?}, shoe size: {:?}", age, size);
+
+    if let Err(error) = guess_from_input("250") {
+        report(&error);
+    }
+}
+
+// ValidationError converts into AppError, so ? works here too
+fn guess_from_input(input: &str) -> Result<Guess, AppError> {
+    let guess: Guess = input.parse()?;
+    Ok(guess)
+}
+
+// Custom error types
+// Returning io::Error works while every failure is an I/O failure. As soon as a function can also fail while parsing or validating, we need one error type that can represent all of them.
+// An enum with one variant per kind of failure is the usual answer. Implementing std::error::Error on it lets callers treat it like any other error, and the From impls below let ? convert the underlying errors automatically.
+
+#[derive(Debug)]
+pub enum AppError {
+    Io(io::Error),
+    File(FileError),
+    Parse(ParseIntError),
+    // input that one of the validated types in validation.rs rejected
+    Invalid(ValidationError),
+}
+
+// Display is the message for humans. It only describes this level; the underlying error is exposed through source() so it isn't printed twice.
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Io(_) => write!(f, "I/O operation failed"),
+            AppError::File(_) => write!(f, "file operation failed"),
+            AppError::Parse(_) => write!(f, "could not parse number"),
+            AppError::Invalid(_) => write!(f, "invalid input"),
+        }
+    }
+}
+
+// source returns the lower-level error that caused this one, if any. Following source() repeatedly walks the whole cause chain.
+impl error::Error for AppError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AppError::Io(e) => Some(e),
+            AppError::File(e) => Some(e),
+            AppError::Parse(e) => Some(e),
+            AppError::Invalid(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(error: io::Error) -> Self {
+        AppError::Io(error)
+    }
+}
+
+impl From<ParseIntError> for AppError {
+    fn from(error: ParseIntError) -> Self {
+        AppError::Parse(error)
+    }
+}
+
+impl From<ValidationError> for AppError {
+    fn from(error: ValidationError) -> Self {
+        AppError::Invalid(error)
+    }
+}
+
+// Renders an error followed by every error in its cause chain, one per line.
+pub fn render_report(error: &dyn error::Error) -> String {
+    let mut report = format!("error: {}", error);
+    let mut cause = error.source();
+    while let Some(inner) = cause {
+        report.push_str(&format!("\n  caused by: {}", inner));
+        cause = inner.source();
+    }
+    report
+}
+
+pub fn report(error: &dyn error::Error) {
+    eprintln!("{}", render_report(error));
+}
+
+// ? works on both the io::Error from read_to_string and the ParseIntError from parse, because AppError implements From for each
+fn read_age_from_file(path: &str) -> Result<u32, AppError> {
+    let contents = read_to_string(path)?;
+    parse_age(&contents)
+}
+
+// the range check is Age's, so there is one place that knows what a realistic age is; its ValidationError becomes AppError::Invalid through From
+fn parse_age(text: &str) -> Result<u32, AppError> {
+    let age: u32 = text.trim().parse()?;
+    Ok(Age::new(age)?.value())
+}
+
+fn custom_error_types() {
+    match read_age_from_file("age.txt") {
+        Ok(age) => println!("age is {}", age),
+        Err(error) => report(&error),
+    }
+    for input in ["42", "forty-two", "200"] {
+        match parse_age(input) {
+            Ok(age) => println!("age is {}", age),
+            Err(error) => report(&error),
+        }
+    }
+}
+
+// The file service takes the path as a parameter, creates the file when it is missing and returns a FileError instead of panicking
+fn file_service_example(path: &Path) {
+    let service = FileService::new(FileServiceConfig {
+        default_contents: String::from("hello my people"),
+        ..FileServiceConfig::default()
+    });
+    match service.read_to_string(path) {
+        Ok(contents) => println!("{} contains {:?}", path.display(), contents),
+        Err(error) => report(&AppError::from(error)),
+    }
+
+    let strict = FileService::new(FileServiceConfig {
+        create_missing: false,
+        ..FileServiceConfig::default()
+    });
+    if let Err(error) = strict.open("missing.txt") {
+        println!("nothing was created at {}", error.path().display());
+        report(&AppError::from(error));
+    }
+}
+
+// Transient errors: the flaky reader fails twice before it succeeds, so retrying with a short exponential backoff gets us the contents.
+// A NotFound error is permanent, so retry gives up on it straight away instead of waiting.
+fn retrying_transient_errors() {
+    let policy = RetryPolicy {
+        backoff: Backoff::Exponential {
+            initial: Duration::from_millis(5),
+            factor: 2.0,
+            max: Duration::from_millis(50),
+        },
+        jitter: 0.5,
+        seed: 42,
+        ..RetryPolicy::default()
+    };
+
+    let mut reader = FlakyFileReader::new(vec![ErrorKind::Interrupted, ErrorKind::TimedOut], "weldon");
+    match retry(&policy, &SystemClock, is_transient, || reader.read_to_string()) {
+        Ok(contents) => println!("read {:?} after {} attempts", contents, reader.reads),
+        Err(error) => report(&error),
+    }
+
+    let mut missing = FlakyFileReader::new(vec![ErrorKind::NotFound], "never read");
+    if let Err(error) = retry(&policy, &SystemClock, is_transient, || missing.read_to_string()) {
+        report(&error);
+    }
+}
 
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn question_mark_converts_into_app_error() {
+        assert!(matches!(read_age_from_file("does-not-exist.txt"), Err(AppError::Io(_))));
+        assert!(matches!(parse_age("abc"), Err(AppError::Parse(_))));
+        assert!(matches!(parse_age("151"), Err(AppError::Invalid(ValidationError::OutOfRange { value: 151, .. }))));
+        assert_eq!(parse_age("150").unwrap(), 150);
+        assert_eq!(parse_age(" 30\n").unwrap(), 30);
+    }
+
+    #[test]
+    fn report_includes_the_cause_chain() {
+        let error = parse_age("abc").unwrap_err();
+        assert_eq!(
+            render_report(&error),
+            "error: could not parse number\n  caused by: invalid digit found in string"
+        );
+
+        let error = parse_age("900").unwrap_err();
+        assert_eq!(render_report(&error), "error: invalid input\n  caused by: age must be between 0 and 150, got 900");
+    }
+
+    #[test]
+    fn validation_errors_keep_their_type() {
+        let error = guess_from_input("250").unwrap_err();
+        assert!(matches!(error, AppError::Invalid(ValidationError::OutOfRange { value: 250, .. })), "{:?}", error);
+        assert_eq!(render_report(&error), "error: invalid input\n  caused by: guess must be between 1 and 100, got 250");
+
+        // the ParseIntError behind a ValidationError is part of the chain too
+        let error = guess_from_input("many").unwrap_err();
+        assert_eq!(render_report(&error).lines().count(), 3);
+    }
+}
@@ -0,0 +1,242 @@
+// Event bus
+// closure_traits in closures.rs explains FnOnce, FnMut and Fn, but only ever passes a closure to sort_by_key. An event bus needs all three.
+// Subscribers hand the bus a closure that is called with every published event: an Fn that only looks at the event, an FnMut that keeps state between events, or an FnOnce that is called for the next event only and then dropped.
+// Handlers with a higher priority run first; subscribe returns a SubscriptionId that can be used to unsubscribe again.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn event_bus() {
+    let mut bus = EventBus::new();
+
+    // Fn: only reads the event
+    bus.subscribe(0, |event: &String| println!("log: {}", event));
+
+    // FnMut: changes the count it captured, so it can't be an Fn
+    let mut count = 0;
+    let counter = bus.subscribe_mut(0, move |_: &String| {
+        count += 1;
+        println!("events so far: {}", count);
+    });
+
+    // FnOnce: moves `greeting` out of the closure, so it can only be called once
+    let greeting = String::from("welcome, first event!");
+    bus.subscribe_once(10, move |event: &String| {
+        let message = greeting;
+        println!("{} ({})", message, event);
+    });
+
+    // the bus owns its handlers, so anything a handler shares with the outside has to be shared explicitly
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let recorder = Rc::clone(&seen);
+    bus.subscribe(-10, move |event: &String| recorder.borrow_mut().push(event.len()));
+
+    for event in ["started", "clicked", "stopped"] {
+        let called = bus.publish(&String::from(event));
+        println!("{:?} went to {} handlers", event, called);
+        if event == "clicked" {
+            bus.unsubscribe(counter);
+        }
+    }
+    println!("event lengths: {:?}", seen.borrow());
+    if !bus.is_empty() {
+        println!("{} handlers still subscribed", bus.len());
+    }
+}
+
+// Returned when subscribing, and needed to unsubscribe. Ids are never reused, so an old id can't remove someone else's handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionId(u64);
+
+type Handler<E> = Box<dyn FnMut(&E)>;
+type OnceHandler<E> = Box<dyn FnOnce(&E)>;
+
+// There is no separate variant for Fn: every Fn closure is also an FnMut, so it is stored the same way.
+// An FnOnce has to be moved out of its box to be called, so it sits in an Option that is emptied when it fires.
+enum Callback<E> {
+    Repeating(Handler<E>),
+    Once(Option<OnceHandler<E>>),
+}
+
+struct Subscriber<E> {
+    id: SubscriptionId,
+    priority: i32,
+    callback: Callback<E>,
+}
+
+pub struct EventBus<E> {
+    // ordered by priority, highest first, and by subscription order within a priority
+    subscribers: Vec<Subscriber<E>>,
+    next_id: u64,
+}
+
+impl<E> Default for EventBus<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> EventBus<E> {
+    pub fn new() -> EventBus<E> {
+        EventBus {
+            subscribers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    // A handler that only needs to look at the event.
+    // Handlers are 'static because the bus keeps them after this call returns; to share state with the rest of the program, capture an Rc or Arc.
+    pub fn subscribe(&mut self, priority: i32, handler: impl Fn(&E) + 'static) -> SubscriptionId {
+        self.add(priority, Callback::Repeating(Box::new(handler)))
+    }
+
+    // a handler that changes what it captured, like a counter or a buffer of events
+    pub fn subscribe_mut(&mut self, priority: i32, handler: impl FnMut(&E) + 'static) -> SubscriptionId {
+        self.add(priority, Callback::Repeating(Box::new(handler)))
+    }
+
+    // a handler for the next event only, which is removed once it has been called
+    pub fn subscribe_once(&mut self, priority: i32, handler: impl FnOnce(&E) + 'static) -> SubscriptionId {
+        self.add(priority, Callback::Once(Some(Box::new(handler))))
+    }
+
+    fn add(&mut self, priority: i32, callback: Callback<E>) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        // after every subscriber with the same or a higher priority, so equal priorities run in the order they subscribed
+        let position = self.subscribers.partition_point(|s| s.priority >= priority);
+        self.subscribers.insert(position, Subscriber { id, priority, callback });
+        id
+    }
+
+    // false if the handler was already removed, or was a one-shot handler that has already fired
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.subscribers.len();
+        self.subscribers.retain(|s| s.id != id);
+        self.subscribers.len() < before
+    }
+
+    // Calls every handler with the event, highest priority first, and returns how many were called.
+    pub fn publish(&mut self, event: &E) -> usize {
+        let mut called = 0;
+        self.subscribers.retain_mut(|subscriber| {
+            called += 1;
+            match &mut subscriber.callback {
+                Callback::Repeating(handler) => {
+                    handler(event);
+                    true
+                }
+                Callback::Once(handler) => {
+                    if let Some(handler) = handler.take() {
+                        handler(event);
+                    }
+                    false
+                }
+            }
+        });
+        called
+    }
+
+    pub fn len(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    // a handler that writes its name and the event to the shared log
+    fn logger(log: &Log, name: &'static str) -> impl Fn(&i32) + 'static {
+        let log = Rc::clone(log);
+        move |event| log.borrow_mut().push(format!("{} {}", name, event))
+    }
+
+    #[test]
+    fn calls_handlers_by_priority() {
+        let log = Log::default();
+        let mut bus = EventBus::new();
+        bus.subscribe(0, logger(&log, "first"));
+        bus.subscribe(5, logger(&log, "urgent"));
+        bus.subscribe(-1, logger(&log, "last"));
+        bus.subscribe(0, logger(&log, "second"));
+
+        assert_eq!(bus.publish(&1), 4);
+        assert_eq!(*log.borrow(), ["urgent 1", "first 1", "second 1", "last 1"]);
+
+        // Fn handlers don't change between calls, so they run the same way every time
+        bus.publish(&2);
+        assert_eq!(log.borrow().len(), 8);
+        assert_eq!(log.borrow()[4], "urgent 2");
+    }
+
+    #[test]
+    fn fn_mut_handlers_keep_state_between_events() {
+        let total = Rc::new(Cell::new(0));
+        let mut bus = EventBus::new();
+        let shared = Rc::clone(&total);
+        let mut running_total = 0;
+        bus.subscribe_mut(0, move |event: &i32| {
+            running_total += event;
+            shared.set(running_total);
+        });
+
+        for event in [3, 4, 5] {
+            bus.publish(&event);
+        }
+        assert_eq!(total.get(), 12);
+    }
+
+    #[test]
+    fn fn_once_handlers_fire_once_and_are_removed() {
+        let log = Log::default();
+        let mut bus = EventBus::new();
+        bus.subscribe(0, logger(&log, "always"));
+        let farewell = String::from("once");
+        let shared = Rc::clone(&log);
+        let once = bus.subscribe_once(1, move |event: &i32| {
+            // moving the captured String out of the closure is what makes it FnOnce only
+            let mut message = farewell;
+            message.push_str(&format!(" {}", event));
+            shared.borrow_mut().push(message);
+        });
+        assert_eq!(bus.len(), 2);
+
+        assert_eq!(bus.publish(&1), 2);
+        assert_eq!(bus.publish(&2), 1);
+        assert_eq!(*log.borrow(), ["once 1", "always 1", "always 2"]);
+        assert_eq!(bus.len(), 1);
+        assert!(!bus.unsubscribe(once));
+    }
+
+    #[test]
+    fn unsubscribed_handlers_are_not_called() {
+        let log = Log::default();
+        let mut bus = EventBus::new();
+        let first = bus.subscribe(0, logger(&log, "first"));
+        let second = bus.subscribe_once(0, logger(&log, "second"));
+        bus.subscribe(0, logger(&log, "third"));
+
+        assert!(bus.unsubscribe(first));
+        assert!(!bus.unsubscribe(first));
+        assert!(bus.unsubscribe(second));
+        bus.publish(&7);
+        assert_eq!(*log.borrow(), ["third 7"]);
+
+        // ids are not reused after a handler is removed
+        let fourth = bus.subscribe(0, logger(&log, "fourth"));
+        assert_ne!(fourth, first);
+        assert_ne!(fourth, second);
+
+        let mut empty: EventBus<i32> = EventBus::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.publish(&0), 0);
+    }
+}
@@ -0,0 +1,271 @@
+// A small file service built on the error handling lessons.
+// The examples in error_handling.rs hard-code "hello.txt" and panic when something goes wrong. Here every path is a parameter, missing files can be created with configurable default contents, transient failures are retried, and every failure comes back as a FileError that says which file and which step was involved.
+
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::clock::SystemClock;
+use crate::error_handling::AppError;
+use crate::retry::{is_transient, retry, Backoff, RetryPolicy};
+
+pub struct FileServiceConfig {
+    pub create_missing: bool,
+    pub default_contents: String,
+    pub max_attempts: u32,
+    pub retry_delay: Duration,
+}
+
+impl Default for FileServiceConfig {
+    fn default() -> Self {
+        FileServiceConfig {
+            create_missing: true,
+            default_contents: String::new(),
+            max_attempts: 3,
+            retry_delay: Duration::from_millis(10),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FileError {
+    NotFound { path: PathBuf },
+    Create { path: PathBuf, source: io::Error },
+    Open { path: PathBuf, source: io::Error },
+    Read { path: PathBuf, source: io::Error },
+}
+
+impl FileError {
+    pub fn path(&self) -> &Path {
+        match self {
+            FileError::NotFound { path }
+            | FileError::Create { path, .. }
+            | FileError::Open { path, .. }
+            | FileError::Read { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileError::NotFound { path } => write!(f, "{} does not exist", path.display()),
+            FileError::Create { path, .. } => write!(f, "could not create {}", path.display()),
+            FileError::Open { path, .. } => write!(f, "could not open {}", path.display()),
+            FileError::Read { path, .. } => write!(f, "could not read {}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FileError::NotFound { .. } => None,
+            FileError::Create { source, .. }
+            | FileError::Open { source, .. }
+            | FileError::Read { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<FileError> for AppError {
+    fn from(error: FileError) -> Self {
+        AppError::File(error)
+    }
+}
+
+pub struct FileService {
+    config: FileServiceConfig,
+}
+
+impl FileService {
+    pub fn new(config: FileServiceConfig) -> FileService {
+        FileService { config }
+    }
+
+    // Opens the file, creating it with the default contents first when it is missing and create_missing is set.
+    pub fn open(&self, path: impl AsRef<Path>) -> Result<File, FileError> {
+        let path = path.as_ref();
+        match self.with_retries(|| File::open(path)) {
+            Ok(file) => Ok(file),
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                if !self.config.create_missing {
+                    return Err(FileError::NotFound {
+                        path: path.to_path_buf(),
+                    });
+                }
+                self.create(path)?;
+                self.with_retries(|| File::open(path))
+                    .map_err(|source| FileError::Open {
+                        path: path.to_path_buf(),
+                        source,
+                    })
+            }
+            Err(source) => Err(FileError::Open {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    pub fn read_to_string(&self, path: impl AsRef<Path>) -> Result<String, FileError> {
+        let path = path.as_ref();
+        let mut file = self.open(path)?;
+        self.read_all(&mut file).map_err(|source| FileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    // A read that fails halfway has already moved the file forward, so every attempt goes back to the start
+    fn read_all(&self, reader: &mut (impl Read + Seek)) -> io::Result<String> {
+        let mut contents = String::new();
+        self.with_retries(|| {
+            contents.clear();
+            reader.seek(SeekFrom::Start(0))?;
+            reader.read_to_string(&mut contents)
+        })?;
+        Ok(contents)
+    }
+
+    // create_new fails if another process created the file in the meantime; that file is just as good as ours, so AlreadyExists is not an error.
+    // A write that fails removes the file again, otherwise the next attempt would find our half-written file and take it for a finished one.
+    fn create(&self, path: &Path) -> Result<(), FileError> {
+        let result = self.with_retries(|| {
+            let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+            file.write_all(self.config.default_contents.as_bytes())
+                .inspect_err(|_| {
+                    let _ = fs::remove_file(path);
+                })
+        });
+        match result {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => Ok(()),
+            Err(source) => Err(FileError::Create {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    // retry::is_transient decides which errors are tried again; anything else is returned straight away.
+    fn with_retries<T>(&self, operation: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let policy = RetryPolicy {
+            max_attempts: self.config.max_attempts,
+            backoff: Backoff::Fixed(self.config.retry_delay),
+            ..RetryPolicy::default()
+        };
+        retry(&policy, &SystemClock, is_transient, operation).map_err(|e| e.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::io::Cursor;
+
+    fn service(create_missing: bool) -> FileService {
+        FileService::new(FileServiceConfig {
+            create_missing,
+            default_contents: String::from("weldon"),
+            retry_delay: Duration::ZERO,
+            ..FileServiceConfig::default()
+        })
+    }
+
+    #[test]
+    fn creates_missing_files_with_default_contents() {
+        let dir = TempDir::new("create");
+        let path = dir.path().join("hello.txt");
+
+        assert_eq!(service(true).read_to_string(&path).unwrap(), "weldon");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "weldon");
+
+        // an existing file is never overwritten
+        fs::write(&path, "hello my people").unwrap();
+        assert_eq!(service(true).read_to_string(&path).unwrap(), "hello my people");
+    }
+
+    #[test]
+    fn returns_typed_errors_instead_of_panicking() {
+        let dir = TempDir::new("errors");
+        let missing = dir.path().join("missing.txt");
+
+        let error = service(false).read_to_string(&missing).unwrap_err();
+        assert!(matches!(error, FileError::NotFound { .. }));
+        assert_eq!(error.path(), missing.as_path());
+
+        let no_parent = dir.path().join("no-such-dir").join("hello.txt");
+        let error = service(true).open(&no_parent).unwrap_err();
+        assert!(matches!(error, FileError::Create { .. }));
+        assert!(error.to_string().starts_with("could not create"));
+    }
+
+    #[test]
+    fn retries_transient_errors_only() {
+        let service = service(true);
+
+        let mut calls = 0;
+        let result = service.with_retries(|| {
+            calls += 1;
+            if calls < 3 {
+                Err(io::Error::from(ErrorKind::Interrupted))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: io::Result<()> = service.with_retries(|| {
+            calls += 1;
+            Err(io::Error::from(ErrorKind::PermissionDenied))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        let result: io::Result<()> = service.with_retries(|| {
+            calls += 1;
+            Err(io::Error::from(ErrorKind::TimedOut))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    // reads a few bytes at a time, and fails once when it gets to `fail_at`
+    struct FailsPartway {
+        inner: Cursor<&'static [u8]>,
+        fail_at: Option<u64>,
+    }
+
+    impl Read for FailsPartway {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.fail_at.is_some_and(|at| self.inner.position() >= at) {
+                self.fail_at = None;
+                return Err(io::Error::from(ErrorKind::TimedOut));
+            }
+            let n = buf.len().min(2);
+            self.inner.read(&mut buf[..n])
+        }
+    }
+
+    impl Seek for FailsPartway {
+        fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(position)
+        }
+    }
+
+    #[test]
+    fn a_retried_read_starts_from_the_beginning() {
+        let mut reader = FailsPartway {
+            inner: Cursor::new(b"hello my people"),
+            fail_at: Some(6),
+        };
+        assert_eq!(service(true).read_all(&mut reader).unwrap(), "hello my people");
+        assert_eq!(reader.fail_at, None);
+    }
+}
@@ -4,6 +4,7 @@ pub fn generics() {
     main_generics();
     struct_generics();
     struct_generics_methods();
+    option_result_replica();
 }
 
 fn largest_i32(list: &[i32]) -> &i32 {
@@ -48,16 +49,248 @@ struct Point_two<T, U>{
 }
 
 
+// The standard library defines Option and Result as ordinary generic enums, exactly like the two below. The enums here shadow the std ones inside this module, so we rebuild the most common combinators on them to see there is no magic involved: every method is just a match on the variants.
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Option<T>{
     Some(T),
     None
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Result<T, E>{
     Ok(T),
     Err(E)
 }
 
+impl<T> Option<T> {
+    fn is_some(&self) -> bool {
+        matches!(self, Option::Some(_))
+    }
+
+    fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    // turns &Option<T> into Option<&T> so we can look inside without moving the value out
+    fn as_ref(&self) -> Option<&T> {
+        match self {
+            Option::Some(value) => Option::Some(value),
+            Option::None => Option::None,
+        }
+    }
+
+    fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Option<U> {
+        match self {
+            Option::Some(value) => Option::Some(f(value)),
+            Option::None => Option::None,
+        }
+    }
+
+    // like map, but the closure itself may return None, so the result isn't nested as Option<Option<U>>
+    fn and_then<U, F: FnOnce(T) -> Option<U>>(self, f: F) -> Option<U> {
+        match self {
+            Option::Some(value) => f(value),
+            Option::None => Option::None,
+        }
+    }
+
+    fn filter<P: FnOnce(&T) -> bool>(self, predicate: P) -> Option<T> {
+        match self {
+            Option::Some(value) if predicate(&value) => Option::Some(value),
+            _ => Option::None,
+        }
+    }
+
+    fn or_else<F: FnOnce() -> Option<T>>(self, f: F) -> Option<T> {
+        match self {
+            Option::Some(value) => Option::Some(value),
+            Option::None => f(),
+        }
+    }
+
+    fn unwrap_or(self, default: T) -> T {
+        match self {
+            Option::Some(value) => value,
+            Option::None => default,
+        }
+    }
+
+    // the closure is only called when there is no value, so an expensive default is never computed needlessly
+    fn unwrap_or_else<F: FnOnce() -> T>(self, f: F) -> T {
+        match self {
+            Option::Some(value) => value,
+            Option::None => f(),
+        }
+    }
+
+    fn ok_or<E>(self, err: E) -> Result<T, E> {
+        match self {
+            Option::Some(value) => Result::Ok(value),
+            Option::None => Result::Err(err),
+        }
+    }
+
+    fn iter(&self) -> OptionIter<'_, T> {
+        OptionIter { inner: self.as_ref() }
+    }
+}
+
+impl<T, E> Result<T, E> {
+    fn is_ok(&self) -> bool {
+        matches!(self, Result::Ok(_))
+    }
+
+    fn is_err(&self) -> bool {
+        !self.is_ok()
+    }
+
+    fn as_ref(&self) -> Result<&T, &E> {
+        match self {
+            Result::Ok(value) => Result::Ok(value),
+            Result::Err(err) => Result::Err(err),
+        }
+    }
+
+    fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Result<U, E> {
+        match self {
+            Result::Ok(value) => Result::Ok(f(value)),
+            Result::Err(err) => Result::Err(err),
+        }
+    }
+
+    fn map_err<F2, O: FnOnce(E) -> F2>(self, op: O) -> Result<T, F2> {
+        match self {
+            Result::Ok(value) => Result::Ok(value),
+            Result::Err(err) => Result::Err(op(err)),
+        }
+    }
+
+    fn and_then<U, F: FnOnce(T) -> Result<U, E>>(self, f: F) -> Result<U, E> {
+        match self {
+            Result::Ok(value) => f(value),
+            Result::Err(err) => Result::Err(err),
+        }
+    }
+
+    fn unwrap_or_else<F: FnOnce(E) -> T>(self, op: F) -> T {
+        match self {
+            Result::Ok(value) => value,
+            Result::Err(err) => op(err),
+        }
+    }
+
+    // ok and err throw away the other variant, converting a Result into one of our Options
+    fn ok(self) -> Option<T> {
+        match self {
+            Result::Ok(value) => Option::Some(value),
+            Result::Err(_) => Option::None,
+        }
+    }
+
+    fn err(self) -> Option<E> {
+        match self {
+            Result::Ok(_) => Option::None,
+            Result::Err(err) => Option::Some(err),
+        }
+    }
+
+    fn iter(&self) -> OptionIter<'_, T> {
+        OptionIter { inner: self.as_ref().ok() }
+    }
+}
+
+// Iterator support: an Option is a collection of zero or one items, and a Result yields its Ok value only.
+// Iterating by reference hands out a borrowed item, consuming iteration hands out the owned value.
+struct OptionIter<'a, T> {
+    inner: Option<&'a T>,
+}
+
+impl<'a, T> Iterator for OptionIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> std::option::Option<&'a T> {
+        // replace leaves None behind, so the single item is only ever yielded once
+        std::mem::replace(&mut self.inner, Option::None).into()
+    }
+}
+
+struct OptionIntoIter<T> {
+    inner: Option<T>,
+}
+
+impl<T> Iterator for OptionIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> std::option::Option<T> {
+        std::mem::replace(&mut self.inner, Option::None).into()
+    }
+}
+
+impl<T> IntoIterator for Option<T> {
+    type Item = T;
+    type IntoIter = OptionIntoIter<T>;
+
+    fn into_iter(self) -> OptionIntoIter<T> {
+        OptionIntoIter { inner: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Option<T> {
+    type Item = &'a T;
+    type IntoIter = OptionIter<'a, T>;
+
+    fn into_iter(self) -> OptionIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, E> IntoIterator for Result<T, E> {
+    type Item = T;
+    type IntoIter = OptionIntoIter<T>;
+
+    fn into_iter(self) -> OptionIntoIter<T> {
+        OptionIntoIter { inner: self.ok() }
+    }
+}
+
+// Conversions to and from the std types.
+// The ? operator is driven by the Try trait, which is still unstable, so our own enums can't be used with ? directly. Converting with .into() first gives a std Option or Result that ? understands.
+impl<T> From<std::option::Option<T>> for Option<T> {
+    fn from(value: std::option::Option<T>) -> Self {
+        match value {
+            Some(value) => Option::Some(value),
+            None => Option::None,
+        }
+    }
+}
+
+impl<T> From<Option<T>> for std::option::Option<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Option::Some(value) => Some(value),
+            Option::None => None,
+        }
+    }
+}
+
+impl<T, E> From<std::result::Result<T, E>> for Result<T, E> {
+    fn from(value: std::result::Result<T, E>) -> Self {
+        match value {
+            Ok(value) => Result::Ok(value),
+            Err(err) => Result::Err(err),
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for std::result::Result<T, E> {
+    fn from(value: Result<T, E>) -> Self {
+        match value {
+            Result::Ok(value) => Ok(value),
+            Result::Err(err) => Err(err),
+        }
+    }
+}
+
 
 struct Point<T>{
     x:T,
@@ -107,3 +340,131 @@ fn struct_generics_methods(){
 }
 
 
+
+// std's parse returns a std Result, which we convert into our own with Result::from
+fn parse_number(text: &str) -> Result<i32, String> {
+    Result::from(text.trim().parse::<i32>()).map_err(|e| format!("{:?}: {}", text, e))
+}
+
+// ? only works on the std types, so each of our Results is converted with into() before being propagated
+fn add_numbers(a: &str, b: &str) -> std::result::Result<i32, String> {
+    let a: std::result::Result<i32, String> = parse_number(a).into();
+    let b: std::result::Result<i32, String> = parse_number(b).into();
+    Ok(a? + b?)
+}
+
+fn option_result_replica() {
+    let number = parse_number("21");
+    let doubled = number.clone().map(|n| n * 2);
+    println!("parsed {:?}, ok: {}, doubled: {:?}", number, number.is_ok(), doubled);
+
+    let bad = parse_number("abc");
+    println!("error: {:?}, is_err: {}", bad.as_ref().err(), bad.is_err());
+    println!("with fallback: {}", bad.unwrap_or_else(|_| 0));
+
+    let positive = number
+        .clone()
+        .and_then(|n| if n > 0 { Result::Ok(n) } else { Result::Err(String::from("not positive")) })
+        .ok();
+    let even = positive.filter(|n| n % 2 == 0);
+    println!("positive: {:?} (is_some: {}), even: {:?} (is_none: {})", positive, positive.is_some(), even, even.is_none());
+
+    let first_char: Option<char> = Option::from("hello".chars().next());
+    let upper = first_char.and_then(|c| c.to_uppercase().next().into());
+    println!("uppercase first char: {}, code point: {:?}", upper.unwrap_or('?'), first_char.map(|c| c as u32));
+
+    let missing: Option<i32> = Option::None;
+    println!("missing or 7: {}", missing.or_else(|| Option::Some(7)).unwrap_or_else(|| 0));
+    println!("missing as result: {:?}", missing.ok_or("no value"));
+
+    // both types can be iterated, yielding zero or one item
+    let total: i32 = positive.iter().chain(&missing).sum::<i32>() + number.iter().sum::<i32>();
+    let collected: Vec<i32> = positive.into_iter().chain(parse_number("4")).collect();
+    println!("total: {}, collected: {:?}", total, collected);
+
+    let back_to_std: std::option::Option<i32> = positive.into();
+    println!("as std option: {:?}, sum: {:?}", back_to_std, add_numbers("2", "x"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs() -> Vec<std::option::Option<i32>> {
+        vec![Some(-3), Some(0), Some(8), Some(15), None]
+    }
+
+    fn results() -> Vec<std::result::Result<i32, String>> {
+        vec![Ok(-3), Ok(0), Ok(8), Err(String::from("bad")), Err(String::new())]
+    }
+
+    fn halve(n: i32) -> std::option::Option<i32> {
+        if n % 2 == 0 { Some(n / 2) } else { None }
+    }
+
+    // every combinator on our Option must give the same answer as std's Option for the same input
+    #[test]
+    fn option_matches_std() {
+        for std_opt in inputs() {
+            let opt = Option::from(std_opt);
+
+            assert_eq!(opt.is_some(), std_opt.is_some());
+            assert_eq!(opt.is_none(), std_opt.is_none());
+            assert_eq!(Option::from(std_opt.as_ref()), opt.as_ref());
+            assert_eq!(Option::from(std_opt.map(|n| n * 10)), opt.map(|n| n * 10));
+            assert_eq!(
+                Option::from(std_opt.and_then(halve)),
+                opt.and_then(|n| halve(n).into())
+            );
+            assert_eq!(Option::from(std_opt.filter(|n| *n > 0)), opt.filter(|n| *n > 0));
+            assert_eq!(
+                Option::from(std_opt.or_else(|| halve(98))),
+                opt.or_else(|| halve(98).into())
+            );
+            assert_eq!(std_opt.unwrap_or(1), opt.unwrap_or(1));
+            assert_eq!(std_opt.unwrap_or_else(|| i32::from(halve(84).is_some())), opt.unwrap_or_else(|| i32::from(halve(84).is_some())));
+            assert_eq!(Result::from(std_opt.ok_or("none")), opt.ok_or("none"));
+            assert_eq!(std_opt.iter().collect::<Vec<_>>(), opt.iter().collect::<Vec<_>>());
+            assert_eq!(std_opt.into_iter().collect::<Vec<_>>(), opt.into_iter().collect::<Vec<_>>());
+            assert_eq!(std::option::Option::from(opt), std_opt);
+        }
+    }
+
+    #[test]
+    fn result_matches_std() {
+        for std_res in results() {
+            let res = Result::from(std_res.clone());
+
+            assert_eq!(res.is_ok(), std_res.is_ok());
+            assert_eq!(res.is_err(), std_res.is_err());
+            assert_eq!(Result::from(std_res.as_ref()), res.as_ref());
+            assert_eq!(Result::from(std_res.clone().map(|n| n + 1)), res.clone().map(|n| n + 1));
+            assert_eq!(
+                Result::from(std_res.clone().map_err(|e| e.len())),
+                res.clone().map_err(|e| e.len())
+            );
+            assert_eq!(
+                Result::from(std_res.clone().and_then(|n| halve(n).ok_or(String::from("odd")))),
+                res.clone().and_then(|n| halve(n).ok_or(String::from("odd")).into())
+            );
+            assert_eq!(
+                std_res.clone().unwrap_or_else(|e| e.len() as i32),
+                res.clone().unwrap_or_else(|e| e.len() as i32)
+            );
+            assert_eq!(Option::from(std_res.clone().ok()), res.clone().ok());
+            assert_eq!(Option::from(std_res.clone().err()), res.clone().err());
+            assert_eq!(std_res.iter().collect::<Vec<_>>(), res.iter().collect::<Vec<_>>());
+            assert_eq!(
+                std_res.clone().into_iter().collect::<Vec<_>>(),
+                res.clone().into_iter().collect::<Vec<_>>()
+            );
+            assert_eq!(std::result::Result::from(res), std_res);
+        }
+    }
+
+    #[test]
+    fn converts_for_question_mark() {
+        assert_eq!(add_numbers("2", " 40"), Ok(42));
+        assert!(add_numbers("2", "forty").unwrap_err().contains("forty"));
+    }
+}
@@ -12,6 +12,7 @@ pub trait Iterator {
 
 */
 // The Iterator trait only requires implementors to define one method: the next method, which returns one item of the iterator at a time wrapped in Some and, when iteration is over, returns None. 
+// adapters.rs builds chunks, windows, dedup and a few more adapters on nothing but next()
 
 
 // methods that consume the iterator
@@ -36,51 +37,93 @@ fn produce_other_iterators(){
 }
 
 
-#[derive(PartialEq, Debug)]
-struct Shoe {
-    size: u32,
-    style: String,
+use crate::validation::ShoeSize;
+
+// shoe_catalog.rs has a catalog of these that can be searched without giving up the shoes
+#[derive(PartialEq, Debug, Clone)]
+pub struct Shoe {
+    pub size: ShoeSize,
+    pub style: String,
 }
 
-fn shoes_in_size(shoes: Vec<Shoe>, shoe_size: u32) -> Vec<Shoe> {
+fn shoes_in_size(shoes: Vec<Shoe>, shoe_size: ShoeSize) -> Vec<Shoe> {
     shoes.into_iter().filter(|s| s.size == shoe_size).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::comparator::{by, by_key};
+    use crate::shoe_catalog::{Catalog, Filter};
 
     #[test]
     fn filters_by_size() {
         let shoes = vec![
             Shoe {
-                size: 10,
+                size: ShoeSize::new(10).unwrap(),
                 style: String::from("sneaker"),
             },
             Shoe {
-                size: 13,
+                size: ShoeSize::new(13).unwrap(),
                 style: String::from("sandal"),
             },
             Shoe {
-                size: 10,
+                size: ShoeSize::new(10).unwrap(),
                 style: String::from("boot"),
             },
         ];
 
-        let in_my_size = shoes_in_size(shoes, 10);
+        let in_my_size = shoes_in_size(shoes, ShoeSize::new(10).unwrap());
 
         assert_eq!(
             in_my_size,
             vec![
                 Shoe {
-                    size: 10,
+                    size: ShoeSize::new(10).unwrap(),
                     style: String::from("sneaker")
                 },
                 Shoe {
-                    size: 10,
+                    size: ShoeSize::new(10).unwrap(),
                     style: String::from("boot")
                 },
             ]
         );
     }
+
+    #[test]
+    fn catalog_finds_the_same_shoes() {
+        let shoes: Vec<Shoe> = [(10, "sneaker"), (13, "sandal"), (10, "boot")]
+            .into_iter()
+            .map(|(size, style)| Shoe {
+                size: ShoeSize::new(size).unwrap(),
+                style: String::from(style),
+            })
+            .collect();
+        let catalog = Catalog::new(&shoes);
+
+        for size in [10, 13, 7] {
+            let in_my_size = shoes_in_size(shoes.clone(), ShoeSize::new(size).unwrap());
+            assert!(in_my_size.iter().eq(catalog.in_size(ShoeSize::new(size).unwrap())));
+            assert!(in_my_size.iter().eq(catalog.query().filter(Filter::size_in(size..=size)).iter()));
+        }
+        assert_eq!(shoes.len(), 3);
+    }
+
+    #[test]
+    fn sorts_by_size_then_style() {
+        let mut shoes: Vec<Shoe> = [(10, "sneaker"), (13, "sandal"), (10, "boot"), (8, "sandal")]
+            .into_iter()
+            .map(|(size, style)| Shoe {
+                size: ShoeSize::new(size).unwrap(),
+                style: String::from(style),
+            })
+            .collect();
+
+        by_key(|s: &Shoe| s.size)
+            .reversed()
+            .then(by(|a: &Shoe, b: &Shoe| a.style.cmp(&b.style)))
+            .sort(&mut shoes);
+        let order: Vec<(u32, &str)> = shoes.iter().map(|s| (s.size.value(), s.style.as_str())).collect();
+        assert_eq!(order, [(13, "sandal"), (10, "boot"), (10, "sneaker"), (8, "sandal")]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,159 @@
+// Running lessons behind a panic boundary
+// A panic in one lesson (an unwrap on a missing file, an explicit panic!) normally unwinds all the way out of main and the remaining lessons never run.
+// std::panic::catch_unwind stops the unwinding at a boundary we choose and hands us the panic as an Err, so a panic can be turned into an ordinary value: here a LessonFailure. catch_unwind is meant for boundaries like this one, not as a general try/catch; inside a lesson, Result is still the way to report errors.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic;
+use std::process::ExitCode;
+use std::sync::Once;
+
+#[derive(Debug)]
+pub struct LessonFailure {
+    pub lesson: &'static str,
+    pub message: String,
+    pub location: Option<String>,
+}
+
+// The panic hook runs on the thread that panicked, before unwinding starts. It is the only place the panic location is available, so it leaves it here for run() to pick up.
+thread_local! {
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+// The hook is global to the process, so it is installed once, from main, rather than swapped in and out around every lesson (which would race with panics on other threads).
+// It records the location and then calls the hook that was there before, so the usual panic message is still printed.
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+            PANIC_LOCATION.with(|cell| *cell.borrow_mut() = location);
+            previous_hook(info);
+        }));
+    });
+}
+
+pub struct LessonRunner {
+    passed: usize,
+    failures: Vec<LessonFailure>,
+}
+
+impl Default for LessonRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LessonRunner {
+    pub fn new() -> LessonRunner {
+        LessonRunner {
+            passed: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    // The location is only known when install_panic_hook has been called; without it a failure still has its message
+    pub fn run(&mut self, lesson: &'static str, body: fn()) {
+        PANIC_LOCATION.with(|cell| cell.borrow_mut().take());
+        match panic::catch_unwind(body) {
+            Ok(()) => self.passed += 1,
+            Err(payload) => self.failures.push(LessonFailure {
+                lesson,
+                message: panic_message(payload.as_ref()),
+                location: PANIC_LOCATION.with(|cell| cell.borrow_mut().take()),
+            }),
+        }
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn print_summary(&self) {
+        println!(
+            "lessons: {} passed, {} failed",
+            self.passed,
+            self.failures.len()
+        );
+        for failure in &self.failures {
+            println!(
+                "  FAILED {} at {}: {}",
+                failure.lesson,
+                failure.location.as_deref().unwrap_or("<unknown location>"),
+                failure.message
+            );
+        }
+    }
+
+    // returning an ExitCode from main sets the process exit status, so scripts can tell that a lesson failed
+    pub fn exit_code(&self) -> ExitCode {
+        if self.all_passed() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// panic!("literal") carries a &str and panic!("{}", x) carries a String; any other payload type comes from panic_any and has no message we can show
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("<non-string panic payload>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passing_lesson() {}
+
+    fn failing_lesson() {
+        let values: Vec<i32> = Vec::new();
+        panic!("no values left, got {:?}", values);
+    }
+
+    fn unwrapping_lesson() {
+        let values = [1, 2, 3];
+        values.iter().find(|v| **v > 3).expect("the value should be present");
+    }
+
+    #[test]
+    fn keeps_running_after_a_panic() {
+        install_panic_hook();
+        let mut runner = LessonRunner::new();
+        runner.run("passing", passing_lesson);
+        runner.run("failing", failing_lesson);
+        runner.run("unwrapping", unwrapping_lesson);
+        runner.run("passing again", passing_lesson);
+
+        assert!(!runner.all_passed());
+        assert_eq!(runner.passed, 2);
+
+        let failures = &runner.failures;
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].lesson, "failing");
+        assert_eq!(failures[0].message, "no values left, got []");
+        assert!(failures[0]
+            .location
+            .as_deref()
+            .is_some_and(|l| l.starts_with("src/lesson_runner.rs:")));
+        assert_eq!(failures[1].lesson, "unwrapping");
+        assert_eq!(failures[1].message, "the value should be present");
+    }
+
+    #[test]
+    fn all_passed_when_nothing_panics() {
+        let mut runner = LessonRunner::new();
+        runner.run("passing", passing_lesson);
+        assert!(runner.all_passed());
+        assert_eq!(runner.passed, 1);
+    }
+}
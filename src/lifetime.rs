@@ -1,5 +1,7 @@
 // lifetimes ensure that references are valid as long as we need them to be.
 
+use crate::text;
+
 //preventing dangling references with lifrtimes
 // The main aim of lifetimes is to prevent dangling references, which cause a program to reference data other than the data it’s intended to reference.
 
@@ -8,6 +10,7 @@
 
 pub fn lifetimes_examples(){
     lifetimes_fn();
+    lifetime_structs();
 }
 
 fn lifrtime_example() {
@@ -63,9 +66,15 @@ fn longest2<'a>(x: &'a str, y: &'a str)->&'a str{
 fn lifetime_structs(){
     let novel = String::from("callme Weldon. Some time back ...");
     let first_sentence = novel.split('.').next().expect("could not find a .");
+    // text::sentences does the same split for every sentence, and the slices it returns borrow from novel just like first_sentence
+    let second_sentence = text::sentences(&novel).nth(1).expect("could not find a second sentence");
+    println!("{} / {}", first_sentence, second_sentence);
     let i = ImportantExcerpt{
         part: first_sentence,
     };
+    // i can't outlive novel, because part borrows from it
+    println!("excerpt: {} (level {})", i.part, i.level());
+    println!("{}", i.announce_and_return_part("here is the excerpt again"));
 }
 
 //lifetime Elision
@@ -18,6 +18,33 @@ mod concurrency;
 mod oop;
 mod oop_blog_post;
 mod pattern_matching;
+mod file_service;
+mod lesson_runner;
+mod rng;
+mod clock;
+mod retry;
+mod error_context;
+mod text;
+mod string_building;
+mod text_transform;
+mod cli;
+mod spatial_index;
+mod validation;
+mod spreadsheet;
+mod statistics;
+mod moving_average;
+mod canvas;
+mod ui;
+mod svg;
+mod shirt_inventory;
+mod cacher;
+mod workout;
+mod event_bus;
+mod comparator;
+mod shoe_catalog;
+mod adapters;
+#[cfg(test)]
+mod test_support;
 
 use control_flow::print_string;
 use control_flow::control_flow;
@@ -50,79 +77,142 @@ use concurrency::concurrency;
 use oop::opp;
 use oop_blog_post::post;
 use pattern_matching::patter_matching;
+use lesson_runner::{install_panic_hook, LessonRunner};
+use spatial_index::spatial_index;
+use text::text_processing;
+use string_building::string_building;
+use spreadsheet::spreadsheet;
+use statistics::statistics;
+use moving_average::moving_averages;
+use shirt_inventory::shirt_inventory;
+use cacher::memoization;
+use workout::workout_planner;
+use event_bus::event_bus;
+use shoe_catalog::shoe_catalog;
+use adapters::iterator_adapters;
+
+use std::env;
+use std::process::ExitCode;
+
+// Each lesson runs behind a panic boundary: a panic is reported and the next lesson still runs.
+fn main() -> ExitCode {
+    // with arguments, run a single command instead of the lessons (see cli.rs)
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&args);
+    }
+
+    install_panic_hook();
+    let mut runner = LessonRunner::new();
 
-fn main() {
     println!("Hello, world!");
-    let str = print_string();
-    println!("The string is {}", str);
+    runner.run("print_string", || println!("The string is {}", print_string()));
 
-    control_flow();
-    control_flow2();
-    control_flow3();
-    control_flow4();
-    while_loop();
-    for_loop();
-    for_loop_rev();
+    runner.run("control_flow", control_flow);
+    runner.run("control_flow2", control_flow2);
+    runner.run("control_flow3", control_flow3);
+    runner.run("control_flow4", control_flow4);
+    runner.run("while_loop", while_loop);
+    runner.run("for_loop", for_loop);
+    runner.run("for_loop_rev", for_loop_rev);
 
     //ownership
-    ownership();
-    return_ownership();
+    runner.run("ownership", ownership);
+    runner.run("return_ownership", return_ownership);
 
     //references
-    reference();
-    mutable_references();
+    runner.run("reference", reference);
+    runner.run("mutable_references", mutable_references);
 
     //slices
-    slices();
+    runner.run("slices", slices);
 
     //structs
-    my_struct();
+    runner.run("my_struct", my_struct);
 
     //methods
-    methods();
-    point_method();
+    runner.run("methods", methods);
+    runner.run("point_method", point_method);
 
     //enumerations
-    null_function();
-    lucky_coin();
+    runner.run("null_function", null_function);
+    runner.run("lucky_coin", lucky_coin);
 
     //collections
-    collections();
+    runner.run("collections", collections);
 
     // error handling
-    errors_handling_rust();
+    runner.run("errors_handling_rust", errors_handling_rust);
 
     //generics
-    generics();
+    runner.run("generics", generics);
 
     //traits
-    traits_demo();
+    runner.run("traits_demo", traits_demo);
 
     //lifetimes
-    lifetimes_examples();
+    runner.run("lifetimes_examples", lifetimes_examples);
 
     //closures
-    closures();
+    runner.run("closures", closures);
 
     //iterators
-    iterators();
+    runner.run("iterators", iterators);
 
     //smart pointers
-    smart_pointers();
+    runner.run("smart_pointers", smart_pointers);
 
     //smartpointer counter references
-    smart_pointers_references();
+    runner.run("smart_pointers_references", smart_pointers_references);
 
     //concurrency
-    concurrency();
+    runner.run("concurrency", concurrency);
 
     //opp
-    opp();
+    runner.run("opp", opp);
 
     // opp blog post example
-    post();
+    runner.run("post", post);
 
     //pattern matching
-    patter_matching();
-}
+    runner.run("patter_matching", patter_matching);
+
+    //spatial index
+    runner.run("spatial_index", spatial_index);
+
+    //text processing
+    runner.run("text_processing", text_processing);
+
+    //building strings
+    runner.run("string_building", string_building);
+
+    //spreadsheet
+    runner.run("spreadsheet", spreadsheet);
+
+    //incremental statistics
+    runner.run("statistics", statistics);
+
+    //windowed averages
+    runner.run("moving_averages", moving_averages);
 
+    //shirt inventory
+    runner.run("shirt_inventory", shirt_inventory);
+
+    //memoization
+    runner.run("memoization", memoization);
+
+    //workout planner
+    runner.run("workout_planner", workout_planner);
+
+    //event bus
+    runner.run("event_bus", event_bus);
+
+    //shoe catalog
+    runner.run("shoe_catalog", shoe_catalog);
+
+    //iterator adapters
+    runner.run("iterator_adapters", iterator_adapters);
+
+    runner.print_summary();
+    runner.exit_code()
+}
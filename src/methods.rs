@@ -1,3 +1,5 @@
+use crate::validation::Age;
+
 pub fn methods() {
     let area: u32 = 800;
     let rect1 = Rectangle {
@@ -23,7 +25,7 @@ pub fn methods() {
 
     println!("associated functions");
     let name = String::from("Weldon");
-    let age = 25;
+    let age = Age::new(25).expect("25 is a valid age");
     let details = Details::personal_details(name, age);
     dbg!(details);
 }
@@ -45,14 +47,14 @@ impl Rectangle {
     }
 }
 
-#[derive(Debug)]
-struct Point {
-    x: f64,
-    y: f64,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
 }
 
 impl Point {
-    fn distance(&self, other: &Point) -> f64 {
+    pub fn distance(&self, other: &Point) -> f64 {
         let x_squared = f64::powi(other.x - self.x, 2); //convert float to int of power 2
         let y_squared = f64::powi(other.y - self.y, 2);
         let result = f64::sqrt(x_squared + y_squared);
@@ -71,11 +73,11 @@ pub fn point_method() {
 #[derive(Debug)]
 struct Details {
     name: String,
-    age: u32,
+    age: Age,
 }
 
 impl Details {
-    fn personal_details(name: String, age: u32) -> Self {
+    fn personal_details(name: String, age: Age) -> Self {
         Self { name, age }
     }
 }
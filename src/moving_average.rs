@@ -0,0 +1,280 @@
+// Windowed averages
+// oop::AveragedCollection averages everything it has ever been given, and its remove only pops the most recent value. For a stream of samples (response times, temperatures) the interesting number is usually the average of the recent ones.
+// The three types here follow the same pattern as AveragedCollection: private fields, and add and average as the only way in and out, so the running totals can never disagree with the samples.
+// - WindowedAverage: the last N samples, kept in a ring buffer
+// - TimeWindowAverage: the samples from the last minute (or any Duration), grouped into time buckets, with the time coming from a Clock
+// - ExponentialMovingAverage: every sample, with older ones counting exponentially less
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+
+pub fn moving_averages() {
+    let samples = [10, 12, 11, 30, 31, 29, 30];
+
+    let mut last_three = WindowedAverage::new(3);
+    let mut ema = match ExponentialMovingAverage::new(0.5) {
+        Ok(ema) => ema,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    };
+    let mut last_minute = TimeWindowAverage::new(SystemClock, Duration::from_secs(60), 6);
+    for sample in samples {
+        let evicted = last_three.add(sample);
+        ema.add(sample);
+        last_minute.add(sample);
+        println!(
+            "added {:>2} (evicted {:?}): last {} {:?}, ema {:?}, last minute {:?}",
+            sample,
+            evicted,
+            last_three.len(),
+            last_three.average(),
+            ema.average(),
+            last_minute.average()
+        );
+    }
+}
+
+// A ring buffer: once the Vec is full, each new sample overwrites the oldest one, found at index `next`, instead of shifting every element like Vec::remove(0) would.
+// The sum is kept as an i64 so it is exact, and a sum of up to 2^32 i32 values can't overflow it.
+pub struct WindowedAverage {
+    samples: Vec<i32>,
+    capacity: usize,
+    next: usize,
+    sum: i64,
+}
+
+impl WindowedAverage {
+    // a capacity of 0 would never hold anything, so it is treated as 1
+    pub fn new(capacity: usize) -> WindowedAverage {
+        let capacity = capacity.max(1);
+        WindowedAverage {
+            samples: Vec::with_capacity(capacity),
+            capacity,
+            next: 0,
+            sum: 0,
+        }
+    }
+
+    // returns the sample that fell out of the window, if the window was full
+    pub fn add(&mut self, value: i32) -> Option<i32> {
+        self.sum += i64::from(value);
+        if self.samples.len() < self.capacity {
+            self.samples.push(value);
+            return None;
+        }
+        let evicted = std::mem::replace(&mut self.samples[self.next], value);
+        self.next = (self.next + 1) % self.capacity;
+        self.sum -= i64::from(evicted);
+        Some(evicted)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn average(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.sum as f64 / self.samples.len() as f64)
+    }
+}
+
+struct Bucket {
+    start: Instant,
+    sum: i64,
+    count: usize,
+}
+
+// Keeping every sample of the last minute could take a lot of memory, so samples are added up per bucket instead: with 6 buckets over a minute, each bucket covers 10 seconds.
+// A whole bucket expires at once when it started more than `window` ago, so the average covers the last `window`, give or take one bucket.
+// The clock is a type parameter: the program passes SystemClock, tests pass a &ManualClock and move time forward themselves.
+pub struct TimeWindowAverage<C: Clock> {
+    clock: C,
+    window: Duration,
+    bucket_width: Duration,
+    buckets: VecDeque<Bucket>,
+}
+
+impl<C: Clock> TimeWindowAverage<C> {
+    pub fn new(clock: C, window: Duration, buckets: u32) -> TimeWindowAverage<C> {
+        TimeWindowAverage {
+            clock,
+            window,
+            bucket_width: window / buckets.max(1),
+            buckets: VecDeque::new(),
+        }
+    }
+
+    pub fn add(&mut self, value: i32) {
+        let now = self.clock.now();
+        while self.buckets.front().is_some_and(|b| self.is_expired(b, now)) {
+            self.buckets.pop_front();
+        }
+
+        match self.buckets.back_mut() {
+            Some(bucket) if now.duration_since(bucket.start) < self.bucket_width => {
+                bucket.sum += i64::from(value);
+                bucket.count += 1;
+            }
+            _ => self.buckets.push_back(Bucket {
+                start: now,
+                sum: i64::from(value),
+                count: 1,
+            }),
+        }
+    }
+
+    fn is_expired(&self, bucket: &Bucket, now: Instant) -> bool {
+        now.duration_since(bucket.start) >= self.window
+    }
+
+    // Buckets that expired since the last add are skipped here rather than removed, so average only needs &self
+    pub fn average(&self) -> Option<f64> {
+        let now = self.clock.now();
+        let (sum, count) = self
+            .buckets
+            .iter()
+            .filter(|b| !self.is_expired(b, now))
+            .fold((0, 0), |(sum, count), b| (sum + b.sum, count + b.count));
+        if count == 0 {
+            return None;
+        }
+        Some(sum as f64 / count as f64)
+    }
+}
+
+// Each new sample moves the average a fraction `alpha` of the way towards it: average = average + alpha * (sample - average).
+// A large alpha follows the samples closely, a small one smooths them out. It needs no buffer at all, just the current average.
+pub struct ExponentialMovingAverage {
+    alpha: f64,
+    average: Option<f64>,
+}
+
+// Returned for an alpha outside (0, 1], NaN included
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidAlpha(pub f64);
+
+impl fmt::Display for InvalidAlpha {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "alpha must be in (0, 1], got {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidAlpha {}
+
+impl ExponentialMovingAverage {
+    // Like Guess::new in validation.rs, the value is checked once here and returned as an error instead of a panic.
+    // Written so that NaN, which fails every comparison, is rejected too.
+    pub fn new(alpha: f64) -> Result<ExponentialMovingAverage, InvalidAlpha> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(InvalidAlpha(alpha));
+        }
+        Ok(ExponentialMovingAverage { alpha, average: None })
+    }
+
+    // the first sample is taken as it is, there is nothing to smooth it with yet
+    pub fn add(&mut self, value: i32) {
+        let value = f64::from(value);
+        self.average = Some(match self.average {
+            Some(average) => average + self.alpha * (value - average),
+            None => value,
+        });
+    }
+
+    pub fn average(&self) -> Option<f64> {
+        self.average
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::rng::XorShiftRng;
+
+    #[test]
+    fn windowed_average_matches_the_last_n_samples() {
+        let mut window = WindowedAverage::new(3);
+        assert_eq!(window.average(), None);
+        assert_eq!(window.add(1), None);
+        assert_eq!(window.add(2), None);
+        assert_eq!(window.add(6), None);
+        assert_eq!(window.average(), Some(3.0));
+        assert_eq!(window.add(7), Some(1));
+        assert_eq!(window.add(8), Some(2));
+        assert_eq!(window.average(), Some(7.0));
+        assert_eq!(window.len(), 3);
+
+        let mut rng = XorShiftRng::seed_from(40);
+        for capacity in [1, 2, 5, 17] {
+            let mut window = WindowedAverage::new(capacity);
+            let mut samples = Vec::new();
+            for _ in 0..200 {
+                let value = rng.next_u64() as i32;
+                window.add(value);
+                samples.push(value);
+                let recent = &samples[samples.len().saturating_sub(capacity)..];
+                let expected = recent.iter().map(|v| i64::from(*v)).sum::<i64>() as f64 / recent.len() as f64;
+                assert_eq!(window.average(), Some(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn time_window_forgets_old_buckets() {
+        let clock = ManualClock::new();
+        let mut average = TimeWindowAverage::new(&clock, Duration::from_secs(60), 6);
+        assert_eq!(average.average(), None);
+
+        average.add(10);
+        clock.advance(Duration::from_secs(5));
+        average.add(20);
+        clock.advance(Duration::from_secs(25));
+        average.add(60);
+        assert_eq!(average.average(), Some(30.0));
+        assert_eq!(average.buckets.len(), 2);
+
+        // the first bucket (10 and 20) started 60 seconds ago
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(average.average(), Some(60.0));
+        average.add(0);
+        assert_eq!(average.buckets.len(), 2);
+        assert_eq!(average.average(), Some(30.0));
+
+        clock.advance(Duration::from_secs(120));
+        assert_eq!(average.average(), None);
+    }
+
+    #[test]
+    fn exponential_average_moves_towards_new_samples() {
+        let mut ema = ExponentialMovingAverage::new(0.5).unwrap();
+        assert_eq!(ema.average(), None);
+        ema.add(10);
+        assert_eq!(ema.average(), Some(10.0));
+        ema.add(20);
+        assert_eq!(ema.average(), Some(15.0));
+        ema.add(20);
+        assert_eq!(ema.average(), Some(17.5));
+
+        let mut last_only = ExponentialMovingAverage::new(1.0).unwrap();
+        last_only.add(3);
+        last_only.add(-4);
+        assert_eq!(last_only.average(), Some(-4.0));
+    }
+
+    #[test]
+    fn exponential_average_rejects_invalid_alpha() {
+        for alpha in [0.0, -0.5, 1.5, f64::INFINITY] {
+            assert_eq!(ExponentialMovingAverage::new(alpha).err(), Some(InvalidAlpha(alpha)));
+        }
+        let error = ExponentialMovingAverage::new(f64::NAN).err().unwrap();
+        assert!(error.0.is_nan());
+        assert_eq!(ExponentialMovingAverage::new(0.0).err().unwrap().to_string(), "alpha must be in (0, 1], got 0");
+    }
+}
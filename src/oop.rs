@@ -7,6 +7,12 @@
 
 //We discussed how to control encapsulation in Chapter 7: we can use the pub keyword to decide which modules, types, functions, and methods in our code should be public, and by default everything else is private. For example, we can define a struct AveragedCollection that has a field containing a vector of i32 values.
 
+use crate::canvas::{Canvas, Rect, Renderer};
+use crate::svg::SvgRenderer;
+use crate::ui::{Event, Key, Layout, Size, Ui};
+use std::cell::RefCell;
+use std::rc::Rc;
+
 pub fn opp(){
     draw_objects()
 }
@@ -48,6 +54,9 @@ impl AveragedCollection{
 
 //We leave the list and average fields private so there is no way for external code to add or remove items to or from the list field directly; otherwise, the average field might become out of sync when the list changes. The average method returns the value in the average field, allowing external code to read the average but not modify it.
 
+// statistics.rs takes the same idea further: Statistics keeps the mean, variance, min, max, median and mode of any integer type up to date in O(log n) per add or remove.
+// moving_average.rs keeps the pattern for averages of recent samples only: the last N, the last minute, or an exponential moving average.
+
 
 // Inheritance as a Type System and as Code Sharing
 
@@ -71,7 +80,32 @@ impl AveragedCollection{
 
 //A trait object in Rust is a way to represent a trait as an opaque type that can be used to store values of different types that implement that trait. It allows you to work with instances of types that implement a specific trait without knowing the concrete types at compile-time.
 pub trait Draw{
-    fn draw(&self);
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect); // draws the component into the given area through a Renderer (see canvas.rs), a character canvas or an SVG document, instead of printing a message
+    fn size(&self) -> (u32, u32); // the width and height the component would like to have. The fields differ per component, so a trait object can only ask for them through a method.
+
+    // Default implementations (see "Inheritance as a Type System and as Code Sharing" above): components that don't react to input, like a label, don't have to write these at all.
+    fn focusable(&self) -> bool {
+        false
+    }
+    fn handle_event(&mut self, _event: &Event, _size: (u32, u32)) -> bool { // returns true if the component used the event. Clicks arrive with x and y relative to the component's top-left corner, and size is the width and height the layout gave the component, which may be less than it asked for.
+        false
+    }
+}
+
+// A Box<dyn Draw> is itself a Draw, so code that is generic over T: Draw (like ui::Ui) also works with a vector of trait objects
+impl<T: Draw + ?Sized> Draw for Box<T> {
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        (**self).draw(renderer, area)
+    }
+    fn size(&self) -> (u32, u32) {
+        (**self).size()
+    }
+    fn focusable(&self) -> bool {
+        (**self).focusable()
+    }
+    fn handle_event(&mut self, event: &Event, size: (u32, u32)) -> bool {
+        (**self).handle_event(event, size)
+    }
 }
 
 pub struct screen{
@@ -85,10 +119,17 @@ pub struct screen{
 
 //On the Screen struct, we’ll define a method named run that will call the draw method on each of its components
 impl screen{
-    pub fn run(&self){ //The run method is defined on the Screen struct. It iterates over the components vector and calls the draw method on each component. This works because all components in the vector implement the Draw trait.
-        for component in self.components.iter(){
-            component.draw();
-        }
+    pub fn run(&self){ //The run method is defined on the Screen struct. It draws each component in the components vector and prints the result. This works because all components in the vector implement the Draw trait.
+        println!("{}", self.render());
+    }
+    pub fn render(&self) -> Canvas{
+        draw_stacked(self.components.iter().map(|component| component.as_ref()), Canvas::new)
+    }
+    pub fn to_svg(&self) -> String{ // the same drawing, exported as an SVG image (see svg.rs)
+        draw_stacked(self.components.iter().map(|component| component.as_ref()), SvgRenderer::new).finish()
+    }
+    pub fn into_ui(self, layout: Layout, width: u32, height: u32) -> Ui<Box<dyn Draw>>{ // the same components, placed by a layout and able to receive events (see ui.rs)
+        Ui::new(self.components, layout, width, height)
     }
 }
 
@@ -101,13 +142,42 @@ where
     T: Draw,
 {
     pub fn run(&self) {
-        for component in self.components.iter() {
-            component.draw();
-        }
+        println!("{}", self.render());
+    }
+    pub fn render(&self) -> Canvas {
+        draw_stacked(self.components.iter().map(|component| component as &dyn Draw), Canvas::new)
+    }
+    pub fn to_svg(&self) -> String {
+        draw_stacked(self.components.iter().map(|component| component as &dyn Draw), SvgRenderer::new).finish()
+    }
+    pub fn into_ui(self, layout: Layout, width: u32, height: u32) -> Ui<T> {
+        Ui::new(self.components, layout, width, height)
     }
 }
 //This restricts us to a Screen instance that has a list of components all of type Button or all of type TextField. 
 
+// Both screens draw their components one below the other, each at the size it asks for, into a renderer just big enough to hold them all.
+// new_renderer creates the renderer once that size is known: Canvas::new for the terminal, SvgRenderer::new for an SVG image.
+fn draw_stacked<'a, R: Renderer>(components: impl Iterator<Item = &'a dyn Draw>, new_renderer: impl FnOnce(u32, u32) -> R) -> R {
+    let components: Vec<&dyn Draw> = components.collect();
+    let width = components.iter().map(|c| c.size().0).max().unwrap_or(0);
+    // saturating, like the layouts in ui.rs, so huge components can't overflow the total
+    let height = components.iter().fold(0u32, |total, c| total.saturating_add(c.size().1));
+    let mut renderer = new_renderer(width, height);
+    let mut y = 0;
+    for component in components {
+        let (width, height) = component.size();
+        component.draw(&mut renderer, Rect::new(0, y, width, height));
+        y = y.saturating_add(height);
+    }
+    renderer
+}
+
+// x position that centers text in area
+fn centered(text: &str, area: Rect) -> u32 {
+    area.x.saturating_add(area.width.saturating_sub(text.chars().count() as u32) / 2)
+}
+
 
 // implementing trait
 
@@ -116,28 +186,160 @@ pub struct Button {
     pub width: u32,
     pub height: u32,
     pub label: String,
+    pub on_click: Option<Box<dyn FnMut()>>, // called when the button is clicked, or Enter or space is pressed while it has focus
 }
 
 //The Button struct represents a button component with fields for width, height, and label. It implements the Draw trait by providing an implementation for the draw method.
 impl Draw for Button {
-    fn draw(&self) {
-        println!("Draw in Button");
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        renderer.border(area);
+        let inner = area.inner();
+        renderer.text(centered(&self.label, inner), inner.y.saturating_add(inner.height.saturating_sub(1) / 2), &self.label, inner.width);
+    }
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+    fn focusable(&self) -> bool {
+        true
+    }
+    fn handle_event(&mut self, event: &Event, _size: (u32, u32)) -> bool {
+        match event {
+            Event::Click { .. } | Event::Key(Key::Enter) | Event::Key(Key::Char(' ')) => {
+                if let Some(on_click) = &mut self.on_click {
+                    on_click();
+                }
+                true
+            }
+            _ => false,
+        }
     }
 }
 
-//The width, height, and label fields on Button will differ from the fields on other components; for example, a TextField type might have those same fields plus a placeholder field. Each of the types we want to draw on the screen will implement the Draw trait but will use different code in the draw method to define how to draw that particular type, as Button has here (without the actual GUI code, as mentioned).
+//The width, height, and label fields on Button will differ from the fields on other components; for example, a TextField type might have those same fields plus a placeholder field. Each of the types we want to draw on the screen will implement the Draw trait but will use different code in the draw method to define how to draw that particular type, as Button has here.
+
 
 
+pub type SelectionCallback = Box<dyn FnMut(usize, &str)>;
+
+pub struct SelectBox {
+    pub width: u32,
+    pub height: u32,
+    pub options: Vec<String>,
+    pub selected: Option<usize>,
+    pub on_change: Option<SelectionCallback>, // called with the index and text of the newly selected option
+}
+
+impl SelectBox {
+    fn select(&mut self, index: usize) {
+        if index >= self.options.len() || self.selected == Some(index) {
+            return;
+        }
+        self.selected = Some(index);
+        if let Some(on_change) = &mut self.on_change {
+            on_change(index, &self.options[index]);
+        }
+    }
 
-struct SelectBox {
-    width: u32,
-    height: u32,
-    options: Vec<String>,
+    // the first option shown when only `rows` fit: the list scrolls just far enough to keep the selected option in the last row
+    fn first_visible(&self, rows: u32) -> usize {
+        self.selected.map_or(0, |selected| (selected + 1).saturating_sub(rows as usize))
+    }
 }
 
+// one option per line inside a border, with a '>' in front of the selected one; when not all options fit, the list scrolls to keep the selected one in view
 impl Draw for SelectBox {
-    fn draw(&self) {
-        println!("Draw in SelectBox");
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        renderer.border(area);
+        let inner = area.inner();
+        let first = self.first_visible(inner.height);
+        for (row, option) in self.options.iter().skip(first).take(inner.height as usize).enumerate() {
+            let y = inner.y.saturating_add(row as u32);
+            if self.selected == Some(first + row) {
+                renderer.text(inner.x, y, ">", inner.width);
+            }
+            renderer.text(inner.x.saturating_add(1), y, option, inner.width.saturating_sub(1));
+        }
+    }
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+    fn focusable(&self) -> bool {
+        true
+    }
+    // the arrow keys move the selection; clicking an option (the rows inside the border, as laid out) selects it
+    fn handle_event(&mut self, event: &Event, (_, height): (u32, u32)) -> bool {
+        let rows = height.saturating_sub(2);
+        match event {
+            Event::Key(Key::Down) => self.select(self.selected.map_or(0, |i| i + 1)),
+            Event::Key(Key::Up) => self.select(self.selected.map_or(0, |i| i.saturating_sub(1))),
+            Event::Click { y, .. } if *y >= 1 && *y <= rows => self.select(self.first_visible(rows) + *y as usize - 1),
+            _ => return false,
+        }
+        true
+    }
+}
+
+// The TextField mentioned above: a one line input box. Until something is typed it shows the placeholder.
+pub struct TextInput {
+    pub width: u32,
+    pub placeholder: String,
+    pub value: String,
+}
+
+impl Draw for TextInput {
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        renderer.border(area);
+        let inner = area.inner();
+        let text = if self.value.is_empty() { &self.placeholder } else { &self.value };
+        // like a real input box, a value that is too long scrolls so its end (where you are typing) stays visible
+        let skip = (text.chars().count() as u32).saturating_sub(inner.width.saturating_sub(1));
+        let visible: String = text.chars().skip(skip as usize).collect();
+        renderer.text(inner.x, inner.y, &visible, inner.width);
+    }
+    fn size(&self) -> (u32, u32) {
+        (self.width, 3)
+    }
+    fn focusable(&self) -> bool {
+        true
+    }
+    fn handle_event(&mut self, event: &Event, _size: (u32, u32)) -> bool {
+        match event {
+            Event::Key(Key::Char(c)) if !c.is_control() => self.value.push(*c),
+            Event::Key(Key::Backspace) => {
+                self.value.pop();
+            }
+            Event::Click { .. } => {}
+            _ => return false,
+        }
+        true
+    }
+}
+
+pub struct Checkbox {
+    pub label: String,
+    pub checked: bool,
+}
+
+// [x] label
+impl Draw for Checkbox {
+    fn draw(&self, renderer: &mut dyn Renderer, area: Rect) {
+        let text = format!("[{}] {}", if self.checked { 'x' } else { ' ' }, self.label);
+        renderer.text(area.x, area.y, &text, area.width);
+    }
+    fn size(&self) -> (u32, u32) {
+        (self.label.chars().count() as u32 + 4, 1)
+    }
+    fn focusable(&self) -> bool {
+        true
+    }
+    fn handle_event(&mut self, event: &Event, _size: (u32, u32)) -> bool {
+        match event {
+            Event::Click { .. } | Event::Key(Key::Char(' ')) => {
+                self.checked = !self.checked;
+                true
+            }
+            _ => false,
+        }
     }
 }
 
@@ -155,11 +357,95 @@ fn draw_objects() {
                     String::from("Maybe"),
                     String::from("No"),
                 ],
+                selected: Some(0),
+                on_change: None,
             },
         ],
     };
     // When you call screen.run(), it will invoke the draw method on each component in the components vector.
     screen.run();
+
+    // The Draw implementations only talk to a Renderer, so the same screen can also be exported as an SVG image
+    println!("{}", screen.to_svg());
+
+    // A Screen<T> can also be given a layout: here the select box is squeezed to 5 rows, and Up selects its first option
+    let mut choices = screen.into_ui(Layout::Column(vec![(Size::Fixed(5), Layout::Component(0))]), 20, 5);
+    choices.dispatch(Event::Key(Key::Up));
+    println!("{}", choices.render());
+
+    // With trait objects, one screen can mix all kinds of components
+    let form = screen {
+        components: vec![
+            Box::new(TextInput {
+                width: 24,
+                placeholder: String::from("Your name"),
+                value: String::new(),
+            }),
+            Box::new(Checkbox {
+                label: String::from("Subscribe to the newsletter"),
+                checked: true,
+            }),
+            Box::new(Button {
+                width: 12,
+                height: 3,
+                label: String::from("OK"),
+                on_click: None,
+            }),
+        ],
+    };
+    form.run();
+
+    // The same kind of form, laid out in a window and driven by events. Callbacks can't borrow from this function for as long as the Ui lives, so they share the log through an Rc<RefCell<..>>.
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let (click_log, change_log) = (Rc::clone(&log), Rc::clone(&log));
+    let window = screen {
+        components: vec![
+            Box::new(TextInput {
+                width: 20,
+                placeholder: String::from("Your name"),
+                value: String::new(),
+            }),
+            Box::new(SelectBox {
+                width: 20,
+                height: 5,
+                options: vec![String::from("Yes"), String::from("Maybe"), String::from("No")],
+                selected: None,
+                on_change: Some(Box::new(move |_, option| change_log.borrow_mut().push(format!("selected {}", option)))),
+            }),
+            Box::new(Button {
+                width: 10,
+                height: 3,
+                label: String::from("Send"),
+                on_click: Some(Box::new(move || click_log.borrow_mut().push(String::from("sent")))),
+            }),
+        ],
+    };
+    let layout = Layout::Padding(1, Box::new(Layout::Column(vec![
+        (Size::Auto, Layout::Component(0)),
+        (Size::Flex(1), Layout::Component(1)),
+        (Size::Fixed(1), Layout::Row(vec![])),
+        (Size::Auto, Layout::Row(vec![(Size::Flex(1), Layout::Row(vec![])), (Size::Auto, Layout::Component(2))])),
+    ])));
+    // an empty Row makes a flexible spacer that pushes the button to the right
+    let mut ui = window.into_ui(layout, 30, 14);
+    for c in "Ferriss".chars() {
+        ui.dispatch(Event::Key(Key::Char(c)));
+    }
+    ui.dispatch(Event::Key(Key::Backspace));
+    ui.dispatch(Event::Key(Key::Tab));
+    ui.dispatch(Event::Key(Key::Down));
+    ui.dispatch(Event::Key(Key::Down));
+    ui.dispatch(Event::Key(Key::Down));
+    ui.dispatch(Event::Key(Key::Up));
+    ui.dispatch(Event::Key(Key::Tab));
+    ui.dispatch(Event::Key(Key::Enter));
+    ui.dispatch(Event::Key(Key::BackTab));
+    ui.dispatch(Event::Click { x: 22, y: 11 });
+    println!("{}", ui.render());
+    println!("focused: {:?}", ui.focused());
+    println!("{}", form.to_svg());
+    println!("{}", ui.to_html("Send feedback"));
+    println!("{:?}", log.borrow());
 }
 
 
@@ -176,4 +462,81 @@ When the draft is done, a review of the post is requested.
 When the post is approved, it gets published.
 Only published blog posts return content to print, so unapproved posts can’t accidentally be published.
 Any other changes attempted on a post should have no effect. For example, if we try to approve a draft blog post before we’ve requested a review, the post should remain an unpublished draft.
-*/
\ No newline at end of file
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(component: &dyn Draw) -> String {
+        draw_stacked(std::iter::once(component), Canvas::new).to_string()
+    }
+
+    #[test]
+    fn renders_button_with_centered_label() {
+        let button = Button { width: 10, height: 5, label: String::from("OK"), on_click: None };
+        assert_eq!(
+            render(&button),
+            "\
++--------+
+|        |
+|   OK   |
+|        |
++--------+"
+        );
+
+        let tiny = Button { width: 6, height: 3, label: String::from("Cancel"), on_click: None };
+        assert_eq!(render(&tiny), "+----+\n|Canc|\n+----+");
+    }
+
+    #[test]
+    fn renders_select_box_options() {
+        let select = SelectBox {
+            width: 10,
+            height: 4,
+            options: vec![String::from("Yes"), String::from("Maybe"), String::from("No")],
+            selected: None,
+            on_change: None,
+        };
+        // "No" doesn't fit in the two rows inside the border
+        assert_eq!(render(&select), "+--------+\n| Yes    |\n| Maybe  |\n+--------+");
+    }
+
+    #[test]
+    fn renders_text_input_and_checkbox() {
+        let mut input = TextInput { width: 12, placeholder: String::from("Your name"), value: String::new() };
+        assert_eq!(render(&input), "+----------+\n|Your name |\n+----------+");
+        input.value = String::from("Ferris the crab");
+        // scrolled to the end, with one cell left for the cursor
+        assert_eq!(render(&input), "+----------+\n| the crab |\n+----------+");
+
+        let checkbox = Checkbox { label: String::from("Remember me"), checked: false };
+        assert_eq!(render(&checkbox), "[ ] Remember me");
+    }
+
+    #[test]
+    fn stacking_huge_components_saturates() {
+        let huge = Button { width: 1, height: u32::MAX, label: String::new(), on_click: None };
+        let small = Button { width: 1, height: 2, label: String::new(), on_click: None };
+        let mut heights = Vec::new();
+        draw_stacked([&huge as &dyn Draw, &small].into_iter(), |width, height| {
+            heights.push((width, height));
+            SvgRenderer::new(width, height)
+        });
+        assert_eq!(heights, [(1, u32::MAX)]);
+    }
+
+    #[test]
+    fn screens_stack_components() {
+        let form = screen {
+            components: vec![
+                Box::new(Checkbox { label: String::from("Agree"), checked: true }),
+                Box::new(Button { width: 7, height: 3, label: String::from("Go"), on_click: None }),
+            ],
+        };
+        assert_eq!(form.render().to_string(), "[x] Agree\n+-----+\n| Go  |\n+-----+");
+
+        let empty: Screen<Button> = Screen { components: vec![] };
+        assert_eq!(empty.render().to_string(), "");
+    }
+}
@@ -0,0 +1,304 @@
+// Retrying transient failures
+// Some errors are permanent (the file doesn't exist, we aren't allowed to read it) and retrying won't help. Others are transient (the read was interrupted, the disk timed out) and the same call will often succeed a moment later.
+// retry calls a closure returning a Result until it succeeds, the error is not retryable, or the attempt/time budget runs out. Between attempts it waits according to a backoff strategy.
+
+use std::fmt;
+use std::io::{self, ErrorKind};
+use std::time::Duration;
+
+use crate::clock::Clock;
+use crate::rng::XorShiftRng;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Backoff {
+    // wait the same amount of time before every retry
+    Fixed(Duration),
+    // wait initial, then initial * factor, initial * factor², ... but never more than max
+    Exponential {
+        initial: Duration,
+        factor: f64,
+        max: Duration,
+    },
+}
+
+impl Backoff {
+    // retry is 1 for the wait after the first failed attempt
+    fn delay(&self, retry: u32) -> Duration {
+        match *self {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential {
+                initial,
+                factor,
+                max,
+            } => {
+                let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+                let seconds = initial.as_secs_f64() * factor.powi(exponent);
+                // try_from_secs_f64 fails when the delay overflows a Duration; the cap applies in that case too
+                Duration::try_from_secs_f64(seconds).unwrap_or(max).min(max)
+            }
+        }
+    }
+}
+
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    // None means there is no time budget, only the attempt limit
+    pub max_elapsed: Option<Duration>,
+    pub backoff: Backoff,
+    // 0.0 waits exactly the backoff delay, 1.0 waits anywhere between zero and the full delay.
+    // Randomising the wait stops many clients that failed at the same moment from all retrying at the same moment too.
+    pub jitter: f64,
+    pub seed: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            max_elapsed: None,
+            backoff: Backoff::Exponential {
+                initial: Duration::from_millis(10),
+                factor: 2.0,
+                max: Duration::from_secs(1),
+            },
+            jitter: 0.0,
+            seed: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GiveUpReason {
+    NotRetryable,
+    OutOfAttempts,
+    OutOfTime,
+}
+
+#[derive(Debug)]
+pub struct RetryError<E> {
+    pub error: E,
+    pub attempts: u32,
+    pub reason: GiveUpReason,
+}
+
+impl<E: fmt::Display> fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.reason {
+            GiveUpReason::NotRetryable => "the error is not retryable",
+            GiveUpReason::OutOfAttempts => "no attempts left",
+            GiveUpReason::OutOfTime => "the time budget ran out",
+        };
+        write!(f, "gave up after {} attempt(s), {}: {}", self.attempts, reason, self.error)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for RetryError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+// The classifier decides which errors are worth retrying. Taking it as a closure keeps retry usable for any error type, not just io::Error.
+pub fn retry<T, E>(
+    policy: &RetryPolicy,
+    clock: &impl Clock,
+    is_retryable: impl Fn(&E) -> bool,
+    mut operation: impl FnMut() -> Result<T, E>,
+) -> Result<T, RetryError<E>> {
+    let start = clock.now();
+    let mut rng = XorShiftRng::seed_from(policy.seed);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        let error = match operation() {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        // clamp lets NaN through, and mul_f64 panics on it, so a jitter that isn't a finite number means no jitter
+        let jitter = if policy.jitter.is_finite() {
+            policy.jitter.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let delay = policy
+            .backoff
+            .delay(attempts)
+            .mul_f64(1.0 - jitter * rng.next_f64());
+        // don't start a wait that would end after the budget; the next attempt could never run in time
+        let out_of_time = policy
+            .max_elapsed
+            .is_some_and(|budget| clock.now().duration_since(start) + delay > budget);
+
+        let reason = if !is_retryable(&error) {
+            GiveUpReason::NotRetryable
+        } else if attempts >= policy.max_attempts {
+            GiveUpReason::OutOfAttempts
+        } else if out_of_time {
+            GiveUpReason::OutOfTime
+        } else {
+            clock.sleep(delay);
+            continue;
+        };
+        return Err(RetryError {
+            error,
+            attempts,
+            reason,
+        });
+    }
+}
+
+// Interrupted, WouldBlock and TimedOut usually go away if we simply try again
+pub fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut
+    )
+}
+
+// A fake file reader that fails a set number of times before returning its contents, standing in for a flaky network drive.
+pub struct FlakyFileReader {
+    failures: Vec<ErrorKind>,
+    contents: String,
+    pub reads: u32,
+}
+
+impl FlakyFileReader {
+    // the reader fails once with each of the given error kinds, in order, and then succeeds
+    pub fn new(failures: Vec<ErrorKind>, contents: &str) -> FlakyFileReader {
+        FlakyFileReader {
+            failures,
+            contents: contents.to_string(),
+            reads: 0,
+        }
+    }
+
+    pub fn read_to_string(&mut self) -> io::Result<String> {
+        self.reads += 1;
+        if self.failures.is_empty() {
+            Ok(self.contents.clone())
+        } else {
+            Err(io::Error::from(self.failures.remove(0)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    fn policy(backoff: Backoff) -> RetryPolicy {
+        RetryPolicy {
+            backoff,
+            ..RetryPolicy::default()
+        }
+    }
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|ms| Duration::from_millis(*ms)).collect()
+    }
+
+    #[test]
+    fn retries_until_the_flaky_reader_succeeds() {
+        let clock = ManualClock::new();
+        let mut reader = FlakyFileReader::new(
+            vec![ErrorKind::Interrupted, ErrorKind::TimedOut, ErrorKind::WouldBlock],
+            "weldon",
+        );
+        let policy = policy(Backoff::Fixed(Duration::from_millis(50)));
+
+        let contents = retry(&policy, &clock, is_transient, || reader.read_to_string());
+        assert_eq!(contents.unwrap(), "weldon");
+        assert_eq!(reader.reads, 4);
+        assert_eq!(*clock.sleeps.borrow(), millis(&[50, 50, 50]));
+    }
+
+    #[test]
+    fn stops_at_errors_that_are_not_retryable() {
+        let clock = ManualClock::new();
+        let mut reader =
+            FlakyFileReader::new(vec![ErrorKind::Interrupted, ErrorKind::NotFound], "weldon");
+
+        let error = retry(&RetryPolicy::default(), &clock, is_transient, || reader.read_to_string())
+            .unwrap_err();
+        assert_eq!(error.reason, GiveUpReason::NotRetryable);
+        assert_eq!(error.attempts, 2);
+        assert_eq!(error.error.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn exponential_backoff_is_capped_and_attempts_are_limited() {
+        let clock = ManualClock::new();
+        let mut reader = FlakyFileReader::new(vec![ErrorKind::Interrupted; 10], "weldon");
+        let policy = policy(Backoff::Exponential {
+            initial: Duration::from_millis(100),
+            factor: 3.0,
+            max: Duration::from_millis(1000),
+        });
+
+        let error = retry(&policy, &clock, is_transient, || reader.read_to_string()).unwrap_err();
+        assert_eq!(error.reason, GiveUpReason::OutOfAttempts);
+        assert_eq!(error.attempts, 5);
+        assert_eq!(*clock.sleeps.borrow(), millis(&[100, 300, 900, 1000]));
+        assert_eq!(error.to_string(), "gave up after 5 attempt(s), no attempts left: operation interrupted");
+    }
+
+    #[test]
+    fn respects_the_elapsed_time_budget() {
+        let clock = ManualClock::new();
+        let mut reader = FlakyFileReader::new(vec![ErrorKind::TimedOut; 10], "weldon");
+        let policy = RetryPolicy {
+            max_attempts: 100,
+            max_elapsed: Some(Duration::from_millis(250)),
+            backoff: Backoff::Fixed(Duration::from_millis(100)),
+            ..RetryPolicy::default()
+        };
+
+        let error = retry(&policy, &clock, is_transient, || {
+            clock.advance(Duration::from_millis(10));
+            reader.read_to_string()
+        })
+        .unwrap_err();
+        assert_eq!(error.reason, GiveUpReason::OutOfTime);
+        assert_eq!(error.attempts, 3);
+        assert_eq!(clock.sleeps.borrow().len(), 2);
+    }
+
+    #[test]
+    fn jitter_is_random_but_reproducible() {
+        let delays = |seed| {
+            let clock = ManualClock::new();
+            let policy = RetryPolicy {
+                jitter: 0.5,
+                seed,
+                ..policy(Backoff::Fixed(Duration::from_millis(100)))
+            };
+            let _ = retry(&policy, &clock, |_: &()| true, || Err::<(), ()>(()));
+            clock.sleeps.take()
+        };
+
+        let first = delays(7);
+        assert_eq!(first.len(), 4);
+        assert!(first
+            .iter()
+            .all(|d| *d > Duration::from_millis(50) && *d <= Duration::from_millis(100)));
+        assert_eq!(first, delays(7));
+        assert_ne!(first, delays(8));
+    }
+
+    #[test]
+    fn jitter_that_is_not_finite_is_ignored() {
+        for jitter in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let clock = ManualClock::new();
+            let policy = RetryPolicy {
+                jitter,
+                max_attempts: 3,
+                ..policy(Backoff::Fixed(Duration::from_millis(100)))
+            };
+            let _ = retry(&policy, &clock, |_: &()| true, || Err::<(), ()>(()));
+            assert_eq!(*clock.sleeps.borrow(), millis(&[100, 100]));
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// A small seedable pseudo-random number generator.
+// The standard library doesn't ship a random number generator, so the lessons that need "random" data use this xorshift64* generator instead.
+// Given the same seed it always produces the same sequence of numbers, which keeps the examples and the tests reproducible.
+
+pub struct XorShiftRng {
+    state: u64,
+}
+
+impl XorShiftRng {
+    // xorshift gets stuck on a state of zero, so a zero seed is swapped for a fixed non-zero constant.
+    pub fn seed_from(seed: u64) -> XorShiftRng {
+        let state = if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed };
+        XorShiftRng { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // uniform value in [0, 1), built from the top 53 bits so every value is exactly representable as an f64
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range_f64(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+}
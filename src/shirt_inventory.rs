@@ -0,0 +1,288 @@
+// Shirt inventory
+// closures::Inventory gives away the user's preferred color or, with unwrap_or_else and a closure, the most stocked one. But its shirts never run out, it only knows Red and Blue, and a tie always goes to Blue.
+// This Inventory tracks real stock per SKU (color and size), takes shirts out of stock when they're given away, can be restocked, and keeps a history of every change.
+// How ties are broken is a closure too, passed in by whoever creates the inventory.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::closures::SHirtColor;
+
+pub fn shirt_inventory() {
+    // the book's rule: when both colors are equally stocked, Blue wins
+    let mut store = Inventory::new().with_tie_breaker(|a: &SHirtColor, b: &SHirtColor| {
+        (*a == SHirtColor::Blue).cmp(&(*b == SHirtColor::Blue))
+    });
+    let deliveries = [
+        (SHirtColor::Red, ShirtSize::M, 2),
+        (SHirtColor::Blue, ShirtSize::M, 2),
+        (SHirtColor::Red, ShirtSize::L, 1),
+        (SHirtColor::Blue, ShirtSize::S, 1),
+        (SHirtColor::Blue, ShirtSize::S, u32::MAX),
+    ];
+    for (color, size, quantity) in deliveries {
+        if let Err(error) = store.restock(Sku::new(color, size), quantity) {
+            println!("{}", error);
+        }
+    }
+
+    let requests = [
+        (Some(SHirtColor::Red), ShirtSize::M),
+        (None, ShirtSize::M),
+        (None, ShirtSize::M),
+        (None, ShirtSize::L),
+        (Some(SHirtColor::Blue), ShirtSize::L),
+        (None, ShirtSize::XL),
+    ];
+    for (preference, size) in requests {
+        match store.giveaway(preference, size) {
+            Ok(sku) => println!("The user with preference {:?} in {:?} gets {}", preference, size, sku),
+            Err(error) => println!("The user with preference {:?} in {:?} gets nothing: {}", preference, size, error),
+        }
+    }
+    let blue_m = Sku::new(SHirtColor::Blue, ShirtSize::M);
+    println!("{} {} shirts left", store.quantity(&blue_m), blue_m);
+    for transaction in store.history() {
+        println!("{:?}", transaction);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShirtSize {
+    S,
+    M,
+    L,
+    XL,
+}
+
+// A stock keeping unit: one kind of shirt the store can have in stock.
+// The color is a type parameter, so the inventory works with closures::SHirtColor as well as any other color type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sku<C> {
+    pub color: C,
+    pub size: ShirtSize,
+}
+
+impl<C> Sku<C> {
+    pub fn new(color: C, size: ShirtSize) -> Sku<C> {
+        Sku { color, size }
+    }
+}
+
+impl<C: fmt::Debug> fmt::Display for Sku<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {:?}", self.color, self.size)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InventoryError<C> {
+    // the preferred color isn't in stock in this size
+    OutOfStock(Sku<C>),
+    // no color at all is in stock in this size
+    SizeOutOfStock(ShirtSize),
+    // restocking would take the count past u32::MAX
+    TooMany(Sku<C>),
+}
+
+impl<C: fmt::Debug> fmt::Display for InventoryError<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InventoryError::OutOfStock(sku) => write!(f, "{} is out of stock", sku),
+            InventoryError::SizeOutOfStock(size) => write!(f, "no shirts left in size {:?}", size),
+            InventoryError::TooMany(sku) => write!(f, "can't stock that many more {}", sku),
+        }
+    }
+}
+
+impl<C: fmt::Debug> std::error::Error for InventoryError<C> {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transaction<C> {
+    Restock { sku: Sku<C>, quantity: u32 },
+    Giveaway { sku: Sku<C>, preference: Option<C> },
+}
+
+// Ordering::Greater means the first color should win the tie
+type TieBreaker<C> = Box<dyn Fn(&C, &C) -> Ordering>;
+
+pub struct Inventory<C> {
+    // a Vec rather than a HashMap: an inventory has a handful of SKUs, and keeping them in the order they were first stocked makes the default tie-break predictable
+    stock: Vec<(Sku<C>, u32)>,
+    history: Vec<Transaction<C>>,
+    tie_breaker: TieBreaker<C>,
+}
+
+impl<C: Clone + PartialEq + fmt::Debug> Default for Inventory<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clone + PartialEq + fmt::Debug> Inventory<C> {
+    // Without a tie breaker, the color that was stocked first wins a tie
+    pub fn new() -> Inventory<C> {
+        Inventory {
+            stock: Vec::new(),
+            history: Vec::new(),
+            tie_breaker: Box::new(|_, _| Ordering::Equal),
+        }
+    }
+
+    // The closure is stored in a Box<dyn Fn>, so every inventory can have its own strategy without the closure's type showing up in Inventory's type.
+    // 'static because the inventory may outlive the function that created the closure, so it can't hold on to borrowed values.
+    pub fn with_tie_breaker(mut self, tie_breaker: impl Fn(&C, &C) -> Ordering + 'static) -> Inventory<C> {
+        self.tie_breaker = Box::new(tie_breaker);
+        self
+    }
+
+    // A restock that would overflow the count is refused as a whole, and isn't recorded
+    pub fn restock(&mut self, sku: Sku<C>, quantity: u32) -> Result<(), InventoryError<C>> {
+        match self.stock.iter_mut().find(|(s, _)| *s == sku) {
+            Some((_, count)) => match count.checked_add(quantity) {
+                Some(total) => *count = total,
+                None => return Err(InventoryError::TooMany(sku)),
+            },
+            None => self.stock.push((sku.clone(), quantity)),
+        }
+        self.history.push(Transaction::Restock { sku, quantity });
+        Ok(())
+    }
+
+    pub fn quantity(&self, sku: &Sku<C>) -> u32 {
+        self.stock.iter().find(|(s, _)| s == sku).map_or(0, |(_, count)| *count)
+    }
+
+    // Like closures::Inventory::giveaway: the preferred color if there is one, otherwise the most stocked color. Either way the shirt has to be in stock in the requested size.
+    pub fn giveaway(&mut self, preference: Option<C>, size: ShirtSize) -> Result<Sku<C>, InventoryError<C>> {
+        let color = match &preference {
+            Some(color) => color.clone(),
+            None => self.most_stocked(size).ok_or(InventoryError::SizeOutOfStock(size))?,
+        };
+        let sku = Sku::new(color, size);
+        match self.stock.iter_mut().find(|(s, count)| *s == sku && *count > 0) {
+            Some((_, count)) => *count -= 1,
+            None => return Err(InventoryError::OutOfStock(sku)),
+        }
+        self.history.push(Transaction::Giveaway {
+            sku: sku.clone(),
+            preference,
+        });
+        Ok(sku)
+    }
+
+    // the color with the most shirts left in this size, with ties going to the color the tie breaker prefers
+    pub fn most_stocked(&self, size: ShirtSize) -> Option<C> {
+        let mut best: Option<(&C, u32)> = None;
+        for (sku, count) in self.stock.iter().filter(|(sku, count)| sku.size == size && *count > 0) {
+            let better = match best {
+                None => true,
+                Some((color, most)) => *count > most || (*count == most && (self.tie_breaker)(&sku.color, color) == Ordering::Greater),
+            };
+            if better {
+                best = Some((&sku.color, *count));
+            }
+        }
+        best.map(|(color, _)| color.clone())
+    }
+
+    pub fn history(&self) -> &[Transaction<C>] {
+        &self.history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_away_preferred_or_most_stocked_shirts() {
+        let mut store = Inventory::new();
+        store.restock(Sku::new("green", ShirtSize::M), 1).unwrap();
+        store.restock(Sku::new("black", ShirtSize::M), 2).unwrap();
+        store.restock(Sku::new("green", ShirtSize::M), 2).unwrap();
+
+        assert_eq!(store.giveaway(None, ShirtSize::M), Ok(Sku::new("green", ShirtSize::M)));
+        assert_eq!(store.quantity(&Sku::new("green", ShirtSize::M)), 2);
+        assert_eq!(store.giveaway(Some("black"), ShirtSize::M), Ok(Sku::new("black", ShirtSize::M)));
+        assert_eq!(store.quantity(&Sku::new("black", ShirtSize::M)), 1);
+        assert_eq!(store.quantity(&Sku::new("black", ShirtSize::XL)), 0);
+    }
+
+    #[test]
+    fn reports_out_of_stock() {
+        let mut store = Inventory::new();
+        store.restock(Sku::new(SHirtColor::Red, ShirtSize::S), 1).unwrap();
+
+        assert_eq!(store.giveaway(None, ShirtSize::S), Ok(Sku::new(SHirtColor::Red, ShirtSize::S)));
+        assert_eq!(
+            store.giveaway(Some(SHirtColor::Red), ShirtSize::S),
+            Err(InventoryError::OutOfStock(Sku::new(SHirtColor::Red, ShirtSize::S)))
+        );
+        assert_eq!(store.giveaway(None, ShirtSize::S), Err(InventoryError::SizeOutOfStock(ShirtSize::S)));
+        assert_eq!(
+            store.giveaway(Some(SHirtColor::Blue), ShirtSize::XL).unwrap_err().to_string(),
+            "Blue XL is out of stock"
+        );
+
+        store.restock(Sku::new(SHirtColor::Red, ShirtSize::S), 3).unwrap();
+        assert!(store.giveaway(Some(SHirtColor::Red), ShirtSize::S).is_ok());
+    }
+
+    #[test]
+    fn refuses_restocks_that_would_overflow() {
+        let mut store = Inventory::new();
+        let red = Sku::new(SHirtColor::Red, ShirtSize::S);
+        store.restock(red.clone(), u32::MAX - 1).unwrap();
+        assert_eq!(store.restock(red.clone(), 2), Err(InventoryError::TooMany(red.clone())));
+        assert_eq!(store.quantity(&red), u32::MAX - 1);
+        assert_eq!(store.history().len(), 1);
+        store.restock(red.clone(), 1).unwrap();
+        assert_eq!(store.quantity(&red), u32::MAX);
+    }
+
+    #[test]
+    fn ties_are_broken_by_the_closure() {
+        let stocked = |store: &mut Inventory<&'static str>| {
+            for color in ["red", "blue", "white"] {
+                store.restock(Sku::new(color, ShirtSize::L), 4).unwrap();
+            }
+        };
+
+        let mut first_stocked = Inventory::new();
+        stocked(&mut first_stocked);
+        assert_eq!(first_stocked.most_stocked(ShirtSize::L), Some("red"));
+
+        let mut alphabetical = Inventory::new().with_tie_breaker(|a: &&str, b: &&str| b.cmp(a));
+        stocked(&mut alphabetical);
+        assert_eq!(alphabetical.most_stocked(ShirtSize::L), Some("blue"));
+
+        let favourite = String::from("white");
+        let mut favourite_first = Inventory::new().with_tie_breaker(move |a: &&str, b: &&str| (*a == favourite).cmp(&(*b == favourite)));
+        stocked(&mut favourite_first);
+        assert_eq!(favourite_first.most_stocked(ShirtSize::L), Some("white"));
+        // more stock still beats the tie breaker
+        favourite_first.giveaway(Some("white"), ShirtSize::L).unwrap();
+        assert_eq!(favourite_first.most_stocked(ShirtSize::L), Some("red"));
+        assert_eq!(favourite_first.most_stocked(ShirtSize::S), None);
+    }
+
+    #[test]
+    fn records_every_transaction() {
+        let mut store = Inventory::new();
+        store.restock(Sku::new(SHirtColor::Blue, ShirtSize::M), 2).unwrap();
+        store.giveaway(None, ShirtSize::M).unwrap();
+        store.giveaway(Some(SHirtColor::Red), ShirtSize::M).unwrap_err();
+        store.giveaway(Some(SHirtColor::Blue), ShirtSize::M).unwrap();
+
+        let blue = Sku::new(SHirtColor::Blue, ShirtSize::M);
+        assert_eq!(
+            store.history(),
+            [
+                Transaction::Restock { sku: blue.clone(), quantity: 2 },
+                Transaction::Giveaway { sku: blue.clone(), preference: None },
+                Transaction::Giveaway { sku: blue, preference: Some(SHirtColor::Blue) },
+            ]
+        );
+    }
+}
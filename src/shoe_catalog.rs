@@ -0,0 +1,306 @@
+// Shoe catalog
+// iterator::shoes_in_size takes the shoes by value and hands back the ones in one exact size, so the Vec can't be searched a second time.
+// A Catalog borrows the shoes instead, and answers as many queries as needed. A Query combines filters (size range, part of the style, joined with and, or and !), an order from comparator.rs and a page, and returns an iterator over the matching shoes.
+// For the most common question, "what is there in my size", the catalog also keeps an index from size to shoes, so it doesn't have to look at every shoe.
+
+use std::collections::BTreeMap;
+use std::ops::{Not, RangeBounds};
+
+use crate::comparator::{by, by_key, Comparator};
+use crate::iterator::Shoe;
+use crate::validation::ShoeSize;
+
+pub fn shoe_catalog() {
+    let shoes: Vec<Shoe> = [(10, "sneaker"), (13, "sandal"), (10, "boot"), (9, "hiking boot"), (11, "sneaker"), (12, "ankle boot")]
+        .into_iter()
+        .filter_map(|(size, style)| Some(Shoe { size: ShoeSize::new(size).ok()?, style: String::from(style) }))
+        .collect();
+    let catalog = Catalog::new(&shoes);
+
+    if let Ok(size) = ShoeSize::new(10) {
+        println!("in size 10: {:?}", catalog.in_size(size).map(|s| &s.style).collect::<Vec<_>>());
+    }
+    if let (Ok(from), Ok(to)) = (ShoeSize::new(11), ShoeSize::new(13)) {
+        println!("sizes 11 to 13: {}", catalog.in_sizes(from..=to).count());
+    }
+
+    // boots from size 10 up, or anything that isn't a sandal in size 9, largest first.
+    // ! applies to everything after it, method calls included, hence the parentheses
+    let big_boots = Filter::style_contains("boot").and(Filter::size_in(10..));
+    let no_sandals_in_9 = (!Filter::style_contains("sandal")).and(Filter::size_in(9..=9));
+    let query = catalog
+        .query()
+        .filter(big_boots.or(no_sandals_in_9))
+        .sort_by(by_key(|s: &Shoe| s.size).reversed());
+    for shoe in query.iter() {
+        println!("{} in size {}", shoe.style, shoe.size.value());
+    }
+
+    let second_page: Vec<&str> = catalog
+        .query()
+        .sort_by(by(|a: &Shoe, b: &Shoe| a.style.cmp(&b.style)))
+        .page(1, 4)
+        .iter()
+        .map(|s| s.style.as_str())
+        .collect();
+    println!("page 2: {:?}", second_page);
+    for (style, shoes) in catalog.query().group_by_style() {
+        println!("{}: {} pairs", style, shoes.len());
+    }
+    // the catalog only borrowed the shoes, so they can still be used
+    println!("{} shoes in total", shoes.len());
+}
+
+type Predicate = Box<dyn Fn(&Shoe) -> bool>;
+
+// A test for a shoe, which can be combined with other filters
+pub struct Filter {
+    matches: Predicate,
+}
+
+impl Filter {
+    pub fn new(matches: impl Fn(&Shoe) -> bool + 'static) -> Filter {
+        Filter {
+            matches: Box::new(matches),
+        }
+    }
+
+    // any range of sizes: 10..=12, 10.., ..=9
+    pub fn size_in(sizes: impl RangeBounds<u32> + 'static) -> Filter {
+        Filter::new(move |shoe| sizes.contains(&shoe.size.value()))
+    }
+
+    // ignores case, so "boot" also finds "Ankle Boot"
+    pub fn style_contains(text: &str) -> Filter {
+        let text = text.to_lowercase();
+        Filter::new(move |shoe| shoe.style.to_lowercase().contains(&text))
+    }
+
+    pub fn and(self, other: Filter) -> Filter {
+        Filter::new(move |shoe| self.matches(shoe) && other.matches(shoe))
+    }
+
+    pub fn or(self, other: Filter) -> Filter {
+        Filter::new(move |shoe| self.matches(shoe) || other.matches(shoe))
+    }
+
+    pub fn matches(&self, shoe: &Shoe) -> bool {
+        (self.matches)(shoe)
+    }
+}
+
+// !filter matches every shoe the filter doesn't
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter::new(move |shoe| !self.matches(shoe))
+    }
+}
+
+pub struct Catalog<'a> {
+    shoes: &'a [Shoe],
+    // positions in `shoes`, in catalog order for every size
+    by_size: BTreeMap<ShoeSize, Vec<usize>>,
+}
+
+impl<'a> Catalog<'a> {
+    pub fn new(shoes: &'a [Shoe]) -> Catalog<'a> {
+        let mut by_size: BTreeMap<ShoeSize, Vec<usize>> = BTreeMap::new();
+        for (i, shoe) in shoes.iter().enumerate() {
+            by_size.entry(shoe.size).or_default().push(i);
+        }
+        Catalog { shoes, by_size }
+    }
+
+    // Like shoes_in_size, but looked up in the index, and without taking the shoes
+    pub fn in_size(&self, size: ShoeSize) -> impl Iterator<Item = &'a Shoe> + '_ {
+        self.by_size
+            .get(&size)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.shoes[i])
+    }
+
+    // smallest size first, and in catalog order within a size
+    pub fn in_sizes(&self, sizes: impl RangeBounds<ShoeSize>) -> impl Iterator<Item = &'a Shoe> + '_ {
+        self.by_size
+            .range(sizes)
+            .flat_map(|(_, positions)| positions)
+            .map(|&i| &self.shoes[i])
+    }
+
+    // a query that matches every shoe, in catalog order, until it is narrowed down
+    pub fn query(&self) -> Query<'a> {
+        Query {
+            shoes: self.shoes,
+            filter: Filter::new(|_| true),
+            order: None,
+            page: None,
+        }
+    }
+}
+
+pub struct Query<'a> {
+    shoes: &'a [Shoe],
+    filter: Filter,
+    order: Option<Comparator<Shoe>>,
+    // (page number, shoes per page)
+    page: Option<(usize, usize)>,
+}
+
+impl<'a> Query<'a> {
+    // calling filter more than once keeps the shoes that match all of the filters
+    pub fn filter(mut self, filter: Filter) -> Query<'a> {
+        self.filter = self.filter.and(filter);
+        self
+    }
+
+    // Sorting is stable, so shoes the comparator finds equal stay in catalog order
+    pub fn sort_by(mut self, order: Comparator<Shoe>) -> Query<'a> {
+        self.order = Some(order);
+        self
+    }
+
+    // Pages are counted from 0. A page past the end is empty.
+    pub fn page(mut self, number: usize, per_page: usize) -> Query<'a> {
+        self.page = Some((number, per_page));
+        self
+    }
+
+    // Without an order the shoes are filtered lazily as the iterator is used; sorting needs all the matches first.
+    pub fn iter(self) -> Box<dyn Iterator<Item = &'a Shoe> + 'a> {
+        let Query { shoes, filter, order, page } = self;
+        let matching = shoes.iter().filter(move |shoe| filter.matches(shoe));
+        let results: Box<dyn Iterator<Item = &'a Shoe>> = match order {
+            None => Box::new(matching),
+            Some(order) => {
+                let mut sorted: Vec<&Shoe> = matching.collect();
+                sorted.sort_by(|a, b| order.compare(a, b));
+                Box::new(sorted.into_iter())
+            }
+        };
+        match page {
+            None => results,
+            Some((number, per_page)) => Box::new(results.skip(number.saturating_mul(per_page)).take(per_page)),
+        }
+    }
+
+    // the results per style, with the styles in alphabetical order
+    pub fn group_by_style(self) -> BTreeMap<&'a str, Vec<&'a Shoe>> {
+        let mut groups: BTreeMap<&str, Vec<&Shoe>> = BTreeMap::new();
+        for shoe in self.iter() {
+            groups.entry(shoe.style.as_str()).or_default().push(shoe);
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shoe(size: u32, style: &str) -> Shoe {
+        Shoe {
+            size: ShoeSize::new(size).unwrap(),
+            style: String::from(style),
+        }
+    }
+
+    fn shoes() -> Vec<Shoe> {
+        vec![
+            shoe(10, "sneaker"),
+            shoe(13, "sandal"),
+            shoe(10, "boot"),
+            shoe(8, "Ankle Boot"),
+            shoe(11, "sneaker"),
+            shoe(9, "sandal"),
+        ]
+    }
+
+    fn styles<'a>(shoes: impl Iterator<Item = &'a Shoe>) -> Vec<(u32, &'a str)> {
+        shoes.map(|s| (s.size.value(), s.style.as_str())).collect()
+    }
+
+    #[test]
+    fn looks_up_sizes_in_the_index() {
+        let shoes = shoes();
+        let catalog = Catalog::new(&shoes);
+        let size = |n| ShoeSize::new(n).unwrap();
+
+        assert_eq!(styles(catalog.in_size(size(10))), [(10, "sneaker"), (10, "boot")]);
+        assert_eq!(catalog.in_size(size(12)).count(), 0);
+        assert_eq!(styles(catalog.in_sizes(size(9)..size(11))), [(9, "sandal"), (10, "sneaker"), (10, "boot")]);
+        assert_eq!(catalog.in_sizes(..).count(), shoes.len());
+
+        // the index and a full scan agree for every size
+        for n in ShoeSize::MIN..=ShoeSize::MAX {
+            let scanned = catalog.query().filter(Filter::size_in(n..=n));
+            assert!(catalog.in_size(size(n)).eq(scanned.iter()));
+        }
+    }
+
+    #[test]
+    fn combines_filters() {
+        let shoes = shoes();
+        let catalog = Catalog::new(&shoes);
+
+        let boots = catalog.query().filter(Filter::style_contains("BOOT"));
+        assert_eq!(styles(boots.iter()), [(10, "boot"), (8, "Ankle Boot")]);
+
+        let small_sneakers = catalog
+            .query()
+            .filter(Filter::size_in(..=10))
+            .filter(Filter::style_contains("sneaker"));
+        assert_eq!(styles(small_sneakers.iter()), [(10, "sneaker")]);
+
+        let big_or_sandal = Filter::size_in(11..).or(Filter::style_contains("sandal"));
+        assert_eq!(styles(catalog.query().filter(big_or_sandal).iter()), [(13, "sandal"), (11, "sneaker"), (9, "sandal")]);
+
+        let anything_but_sneakers = !Filter::style_contains("sneaker");
+        assert_eq!(catalog.query().filter(anything_but_sneakers).iter().count(), 4);
+
+        let custom = Filter::new(|s| s.style.len() > 6).and(!Filter::size_in(10..=10));
+        assert_eq!(styles(catalog.query().filter(custom).iter()), [(8, "Ankle Boot"), (11, "sneaker")]);
+    }
+
+    #[test]
+    fn sorts_and_paginates() {
+        let shoes = shoes();
+        let catalog = Catalog::new(&shoes);
+        let by_size = || by_key(|s: &Shoe| s.size);
+
+        assert_eq!(
+            styles(catalog.query().sort_by(by_size()).iter()),
+            [(8, "Ankle Boot"), (9, "sandal"), (10, "sneaker"), (10, "boot"), (11, "sneaker"), (13, "sandal")]
+        );
+        let pages: Vec<Vec<(u32, &str)>> = (0..4)
+            .map(|page| styles(catalog.query().sort_by(by_size().reversed()).page(page, 4).iter()))
+            .collect();
+        assert_eq!(
+            pages,
+            [
+                vec![(13, "sandal"), (11, "sneaker"), (10, "sneaker"), (10, "boot")],
+                vec![(9, "sandal"), (8, "Ankle Boot")],
+                vec![],
+                vec![],
+            ]
+        );
+        // without an order, pages follow the catalog
+        assert_eq!(styles(catalog.query().page(2, 2).iter()), [(11, "sneaker"), (9, "sandal")]);
+        assert_eq!(catalog.query().page(usize::MAX, 2).iter().count(), 0);
+    }
+
+    #[test]
+    fn groups_by_style() {
+        let shoes = shoes();
+        let catalog = Catalog::new(&shoes);
+
+        let groups = catalog.query().filter(Filter::size_in(9..=11)).group_by_style();
+        let sizes: Vec<(&str, Vec<u32>)> = groups
+            .iter()
+            .map(|(style, shoes)| (*style, shoes.iter().map(|s| s.size.value()).collect()))
+            .collect();
+        assert_eq!(sizes, [("boot", vec![10]), ("sandal", vec![9]), ("sneaker", vec![10, 11])]);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::text;
+
 pub fn slices() {
     let s = String::from("Hello this rust programming language");
     let len = first_word(&s);
@@ -32,10 +34,16 @@ fn string_slices(){
     println!("hello = {}",hello);
     let world = &s[6..11];
     println!("world = {}",world);
+
+    // The ranges above are byte offsets. In "Habari dünya" the ü takes two bytes, so &s[0..9] would end in the middle of it and panic.
+    // text::substring_chars counts chars instead and returns None when the range doesn't fit.
+    let s = String::from("Habari dünya");
+    println!("dünya = {:?}", text::substring_chars(&s, 7..12));
+    println!("too far = {:?}", text::substring_chars(&s, 7..20));
 }
 
+// text::first_word also stops at punctuation and any kind of whitespace, so "Hello, world!" gives "Hello" rather than "Hello,"
 fn print_first_word(s: &str) {
-    let first_space = s.find(' ').unwrap_or(s.len()); // Find the index of the first space or use the length of the string if no space is found
-    let word = &s[..first_space]; // Take a substring from the start of the string up to the first space
+    let word = text::first_word(s).unwrap_or(""); // None when the string has no words at all
     println!("The first word is: {}", word);
 }
\ No newline at end of file
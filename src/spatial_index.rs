@@ -0,0 +1,339 @@
+// Spatial indexes
+// Point::distance in methods.rs compares two points at a time. To answer "which point is closest to this one?" over a large collection, the naive approach measures the distance to every single point, which is O(n) for each query.
+
+// A k-d tree (k-dimensional tree) is a binary tree where every level splits the plane in two along one axis, alternating between x and y. During a search, a whole half of the plane can be skipped once it is provably further away than the best candidate found so far, so a typical query only visits O(log n) nodes.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+use crate::methods::Point;
+use crate::rng::XorShiftRng;
+
+pub fn spatial_index() {
+    kd_tree_queries();
+    benchmark_kd_tree();
+}
+
+// Both the k-d tree and the brute-force search answer the same three questions, so they share a trait. The brute-force version is slow but obviously correct, which makes it the reference the tree is tested against.
+// All results are ordered from the closest point to the furthest one.
+pub trait SpatialIndex {
+    fn nearest(&self, target: &Point) -> Option<&Point>;
+    fn k_nearest(&self, target: &Point, k: usize) -> Vec<&Point>;
+    fn within_radius(&self, target: &Point, radius: f64) -> Vec<&Point>;
+}
+
+pub struct BruteForceIndex {
+    points: Vec<Point>,
+}
+
+impl BruteForceIndex {
+    pub fn new(points: Vec<Point>) -> BruteForceIndex {
+        BruteForceIndex { points }
+    }
+}
+
+impl SpatialIndex for BruteForceIndex {
+    fn nearest(&self, target: &Point) -> Option<&Point> {
+        // f64 only implements PartialOrd because of NaN, total_cmp gives us a total order we can sort and compare with
+        self.points
+            .iter()
+            .min_by(|a, b| a.distance(target).total_cmp(&b.distance(target)))
+    }
+
+    fn k_nearest(&self, target: &Point, k: usize) -> Vec<&Point> {
+        let mut sorted: Vec<&Point> = self.points.iter().collect();
+        sorted.sort_by(|a, b| a.distance(target).total_cmp(&b.distance(target)));
+        sorted.truncate(k);
+        sorted
+    }
+
+    fn within_radius(&self, target: &Point, radius: f64) -> Vec<&Point> {
+        let mut found: Vec<&Point> = self
+            .points
+            .iter()
+            .filter(|p| p.distance(target) <= radius)
+            .collect();
+        found.sort_by(|a, b| a.distance(target).total_cmp(&b.distance(target)));
+        found
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+
+impl Axis {
+    fn of(self, point: &Point) -> f64 {
+        match self {
+            Axis::X => point.x,
+            Axis::Y => point.y,
+        }
+    }
+
+    fn next(self) -> Axis {
+        match self {
+            Axis::X => Axis::Y,
+            Axis::Y => Axis::X,
+        }
+    }
+}
+
+// Every node owns its children through a Box, the same way the cons list in smart_pointers.rs does: a recursive type needs a pointer so its size is known at compile time.
+// Points in the left subtree are <= the node's point on its axis, points in the right subtree are >= it.
+struct KdNode {
+    point: Point,
+    axis: Axis,
+    left: Option<Box<KdNode>>,
+    right: Option<Box<KdNode>>,
+}
+
+pub struct KdTree {
+    root: Option<Box<KdNode>>,
+}
+
+impl KdTree {
+    pub fn new(points: Vec<Point>) -> KdTree {
+        KdTree {
+            root: build(points, Axis::X),
+        }
+    }
+}
+
+// Splitting at the median keeps the tree balanced. select_nth_unstable_by partitions the vector around the median in O(n) without fully sorting it.
+fn build(mut points: Vec<Point>, axis: Axis) -> Option<Box<KdNode>> {
+    if points.is_empty() {
+        return None;
+    }
+    let median = points.len() / 2;
+    points.select_nth_unstable_by(median, |a, b| axis.of(a).total_cmp(&axis.of(b)));
+    let right = points.split_off(median + 1);
+    let point = points.pop()?;
+
+    Some(Box::new(KdNode {
+        point,
+        axis,
+        left: build(points, axis.next()),
+        right: build(right, axis.next()),
+    }))
+}
+
+// A candidate found during a search. BinaryHeap is a max-heap, so ordering candidates by distance keeps the furthest of the current k best on top, ready to be replaced by something closer.
+struct Candidate<'a> {
+    distance: f64,
+    point: &'a Point,
+}
+
+impl PartialEq for Candidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate<'_> {}
+
+impl PartialOrd for Candidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance)
+    }
+}
+
+impl KdNode {
+    fn k_nearest<'a>(&'a self, target: &Point, k: usize, best: &mut BinaryHeap<Candidate<'a>>) {
+        let distance = self.point.distance(target);
+        if best.len() < k {
+            best.push(Candidate { distance, point: &self.point });
+        } else if best.peek().is_some_and(|worst| distance < worst.distance) {
+            best.pop();
+            best.push(Candidate { distance, point: &self.point });
+        }
+
+        // search the side of the split the target is on first, it is the most likely to hold close points
+        let diff = self.axis.of(target) - self.axis.of(&self.point);
+        let (near, far) = if diff < 0.0 {
+            (&self.left, &self.right)
+        } else {
+            (&self.right, &self.left)
+        };
+        if let Some(node) = near {
+            node.k_nearest(target, k, best);
+        }
+
+        // the other side can only hold a closer point if the splitting line itself is closer than the current k-th best
+        let worst = match best.peek() {
+            Some(worst) if best.len() == k => worst.distance,
+            _ => f64::INFINITY,
+        };
+        if diff.abs() <= worst {
+            if let Some(node) = far {
+                node.k_nearest(target, k, best);
+            }
+        }
+    }
+
+    fn within_radius<'a>(&'a self, target: &Point, radius: f64, found: &mut Vec<Candidate<'a>>) {
+        let distance = self.point.distance(target);
+        if distance <= radius {
+            found.push(Candidate { distance, point: &self.point });
+        }
+
+        let diff = self.axis.of(target) - self.axis.of(&self.point);
+        if diff <= radius {
+            if let Some(node) = &self.left {
+                node.within_radius(target, radius, found);
+            }
+        }
+        if diff >= -radius {
+            if let Some(node) = &self.right {
+                node.within_radius(target, radius, found);
+            }
+        }
+    }
+}
+
+impl SpatialIndex for KdTree {
+    fn nearest(&self, target: &Point) -> Option<&Point> {
+        self.k_nearest(target, 1).into_iter().next()
+    }
+
+    fn k_nearest(&self, target: &Point, k: usize) -> Vec<&Point> {
+        let mut best = BinaryHeap::with_capacity(k);
+        if let (Some(root), true) = (&self.root, k > 0) {
+            root.k_nearest(target, k, &mut best);
+        }
+        // into_sorted_vec returns the candidates in ascending order, closest first
+        best.into_sorted_vec().into_iter().map(|c| c.point).collect()
+    }
+
+    fn within_radius(&self, target: &Point, radius: f64) -> Vec<&Point> {
+        let mut found = Vec::new();
+        if let Some(root) = &self.root {
+            root.within_radius(target, radius, &mut found);
+        }
+        found.sort();
+        found.into_iter().map(|c| c.point).collect()
+    }
+}
+
+fn random_points(count: usize, seed: u64) -> Vec<Point> {
+    let mut rng = XorShiftRng::seed_from(seed);
+    (0..count)
+        .map(|_| Point {
+            x: rng.range_f64(0.0, 1000.0),
+            y: rng.range_f64(0.0, 1000.0),
+        })
+        .collect()
+}
+
+fn kd_tree_queries() {
+    let tree = KdTree::new(vec![
+        Point { x: 3.6, y: 8.3 },
+        Point { x: 6.4, y: 4.9 },
+        Point { x: 1.0, y: 1.0 },
+        Point { x: 9.5, y: 9.0 },
+        Point { x: 4.2, y: 6.1 },
+    ]);
+    let target = Point { x: 5.0, y: 5.0 };
+
+    println!("nearest to {:?} is {:?}", target, tree.nearest(&target));
+    println!("2 nearest to {:?} are {:?}", target, tree.k_nearest(&target, 2));
+    println!("within 3.0 of {:?}: {:?}", target, tree.within_radius(&target, 3.0));
+}
+
+// Compares the k-d tree against the brute-force search for 100k random points. The brute-force search has to measure every point for every query, the tree only a handful.
+fn benchmark_kd_tree() {
+    let points = random_points(100_000, 42);
+    let queries = random_points(200, 7);
+
+    let start = Instant::now();
+    let tree = KdTree::new(points.clone());
+    let build_time = start.elapsed();
+    let brute_force = BruteForceIndex::new(points);
+
+    let start = Instant::now();
+    let tree_total: f64 = queries
+        .iter()
+        .filter_map(|q| tree.nearest(q).map(|p| p.distance(q)))
+        .sum();
+    let tree_time = start.elapsed();
+
+    let start = Instant::now();
+    let brute_total: f64 = queries
+        .iter()
+        .filter_map(|q| brute_force.nearest(q).map(|p| p.distance(q)))
+        .sum();
+    let brute_time = start.elapsed();
+
+    println!(
+        "k-d tree: built in {:?}, {} queries in {:?}",
+        build_time,
+        queries.len(),
+        tree_time
+    );
+    println!("brute force: {} queries in {:?}", queries.len(), brute_time);
+    println!(
+        "speed-up: {:.1}x (same answers: {})",
+        brute_time.as_secs_f64() / tree_time.as_secs_f64().max(f64::EPSILON),
+        tree_total == brute_total
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distances(target: &Point, points: &[&Point]) -> Vec<f64> {
+        points.iter().map(|p| p.distance(target)).collect()
+    }
+
+    // property test: for many random point sets and queries, the tree must agree with the brute-force reference
+    #[test]
+    fn kd_tree_matches_brute_force() {
+        for seed in 1..=20 {
+            let points = random_points(300, seed);
+            let tree = KdTree::new(points.clone());
+            let brute_force = BruteForceIndex::new(points);
+
+            for target in random_points(25, seed + 1000) {
+                assert_eq!(
+                    tree.nearest(&target).map(|p| p.distance(&target)),
+                    brute_force.nearest(&target).map(|p| p.distance(&target))
+                );
+                for k in [1, 5, 17] {
+                    assert_eq!(
+                        distances(&target, &tree.k_nearest(&target, k)),
+                        distances(&target, &brute_force.k_nearest(&target, k))
+                    );
+                }
+                for radius in [0.0, 25.0, 120.0] {
+                    assert_eq!(
+                        distances(&target, &tree.within_radius(&target, radius)),
+                        distances(&target, &brute_force.within_radius(&target, radius))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn handles_duplicates_and_small_inputs() {
+        let empty = KdTree::new(vec![]);
+        let origin = Point { x: 0.0, y: 0.0 };
+        assert_eq!(empty.nearest(&origin), None);
+        assert!(empty.k_nearest(&origin, 3).is_empty());
+
+        let same = Point { x: 2.0, y: 2.0 };
+        let tree = KdTree::new(vec![same, same, same]);
+        assert_eq!(tree.k_nearest(&origin, 10), vec![&same, &same, &same]);
+        assert!(tree.k_nearest(&origin, 0).is_empty());
+        assert_eq!(tree.within_radius(&same, 0.0).len(), 3);
+    }
+}
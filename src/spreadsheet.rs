@@ -0,0 +1,974 @@
+// A small spreadsheet
+// collections::SpreadSheetCell shows how an enum lets one Vec hold integers, floats and text. Here the same enum becomes the cells of a real sheet: cells are addressed as A1, B2, AA10, and a Formula variant holds expressions like =A1+B2*3 or =SUM(A1:A10).
+// Every formula records which cells it reads. When a cell changes, only the cells that depend on it are recalculated, in topological order (every cell after the cells it reads), and a formula that would depend on itself is rejected with the cycle spelled out.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::collections::SpreadSheetCell;
+
+pub fn spreadsheet() {
+    let mut sheet = Sheet::new();
+    let inputs = [
+        ("A1", "10"),
+        ("A2", "20"),
+        ("A3", "12.5"),
+        ("B1", "=A1+A2*3"),
+        ("B2", "=SUM(A1:A3)"),
+        ("B3", "=(B1+B2)/2"),
+        ("C1", "Total"),
+    ];
+    for (address, input) in inputs {
+        if let Err(error) = sheet.set_input(address, input) {
+            println!("{}: {}", address, error);
+        }
+    }
+    println!("{}", sheet.values_to_csv());
+
+    // changing A1 recalculates B1 and B2 first, then B3 which reads both
+    match sheet.set_input("A1", "100") {
+        Ok(order) => println!("recalculated: {}", join_refs(&order, ", ")),
+        Err(error) => println!("{}", error),
+    }
+    if let Ok(b3) = CellRef::new(1, 2) {
+        println!("B3 is now {}", sheet.value(b3));
+    }
+
+    if let Err(error) = sheet.set_input("A1", "=B3+1") {
+        println!("A1: {}", error);
+    }
+
+    match Sheet::from_csv("price,quantity,total\n2.5,4,=A2*B2\n") {
+        Ok(imported) => println!("{}{}", imported.to_csv(), imported.values_to_csv()),
+        Err(error) => println!("{}", error),
+    }
+}
+
+// A cell address. Columns and rows are stored zero-based, so A1 is column 0, row 0.
+// Deriving Ord with row before column sorts cells row by row, the order they appear in a CSV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellRef {
+    row: u32,
+    column: u32,
+}
+
+impl CellRef {
+    // Display counts from 1, so u32::MAX itself has no address
+    pub const MAX: u32 = u32::MAX - 1;
+
+    pub fn new(column: u32, row: u32) -> Result<CellRef, SheetError> {
+        if column > CellRef::MAX || row > CellRef::MAX {
+            return Err(SheetError::OutOfBounds { column, row });
+        }
+        Ok(CellRef { row, column })
+    }
+}
+
+// Column letters count in base 26 without a zero digit: A..Z, then AA..AZ, BA.. and so on
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut letters = Vec::new();
+        let mut n = self.column + 1;
+        while n > 0 {
+            let digit = (n - 1) % 26;
+            letters.push((b'A' + digit as u8) as char);
+            n = (n - 1) / 26;
+        }
+        let column: String = letters.iter().rev().collect();
+        write!(f, "{}{}", column, self.row + 1)
+    }
+}
+
+impl FromStr for CellRef {
+    type Err = SheetError;
+
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        let invalid = || SheetError::InvalidAddress(address.to_string());
+        let split = address.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (letters, digits) = address.split_at(split);
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(invalid());
+        }
+
+        let mut column: u32 = 0;
+        for c in letters.chars() {
+            let digit = (c.to_ascii_uppercase() as u8 - b'A') as u32 + 1;
+            column = column.checked_mul(26).and_then(|n| n.checked_add(digit)).ok_or_else(invalid)?;
+        }
+        let row: u32 = digits.parse().map_err(|_| invalid())?;
+        if row == 0 {
+            return Err(invalid());
+        }
+        CellRef::new(column - 1, row - 1)
+    }
+}
+
+fn join_refs(cells: &[CellRef], separator: &str) -> String {
+    cells.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(separator)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Sum,
+    Average,
+    Min,
+    Max,
+}
+
+// The parsed form of a formula. Recursive variants hold their operands in a Box, because an Expr can't contain another Expr directly.
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Cell(CellRef),
+    // only allowed as a function argument, as in SUM(A1:A10)
+    Range(CellRef, CellRef),
+    Negate(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    // every cell this expression reads, including every cell inside a range
+    fn references(&self, cells: &mut Vec<CellRef>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Cell(cell) => cells.push(*cell),
+            Expr::Range(from, to) => cells.extend(range_cells(*from, *to)),
+            Expr::Negate(inner) => inner.references(cells),
+            Expr::Binary(_, left, right) => {
+                left.references(cells);
+                right.references(cells);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|arg| arg.references(cells)),
+        }
+    }
+}
+
+// Every cell of a range becomes a dependency of its formula, so SUM(A1:ZZ100000) would add millions of them; the parser rejects ranges larger than this
+const MAX_RANGE_CELLS: u64 = 10_000;
+
+fn range_size(from: CellRef, to: CellRef) -> u64 {
+    let rows = u64::from(from.row.abs_diff(to.row)) + 1;
+    let columns = u64::from(from.column.abs_diff(to.column)) + 1;
+    rows * columns
+}
+
+// the corners can be given in any order: B3:A1 covers the same cells as A1:B3
+fn range_cells(from: CellRef, to: CellRef) -> impl Iterator<Item = CellRef> {
+    let (rows, columns) = (
+        from.row.min(to.row)..=from.row.max(to.row),
+        from.column.min(to.column)..=from.column.max(to.column),
+    );
+    // both corners are valid cells, so every cell between them is too
+    rows.flat_map(move |row| columns.clone().map(move |column| CellRef { row, column }))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+// A formula keeps its source text, for display and CSV export, next to the parsed expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    source: String,
+    expr: Expr,
+}
+
+impl Formula {
+    // the source may start with '=', as typed into a cell, or not
+    pub fn parse(source: &str) -> Result<Formula, ParseError> {
+        let body = source.strip_prefix('=').unwrap_or(source);
+        let mut parser = Parser {
+            chars: body.chars().collect(),
+            position: 0,
+            depth: 0,
+        };
+        let expr = parser.expression()?;
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek() {
+            return Err(parser.error(format!("unexpected {:?}", c)));
+        }
+        Ok(Formula {
+            source: body.trim().to_string(),
+            expr,
+        })
+    }
+}
+
+// A recursive descent parser. Each method parses one level of precedence and calls the next level for its operands, so * and / bind tighter than + and -:
+//     expression = term (('+' | '-') term)*
+//     term       = factor (('*' | '/') factor)*
+//     factor     = number | cell | function '(' arguments ')' | '-' factor | '(' expression ')'
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    // how many levels deep the expression being parsed will be, see descend
+    depth: usize,
+}
+
+// Parsing, evaluating and dropping an Expr all recurse once per level of the tree, so a formula like =((((...)))) or =1+1+...+1 with thousands of levels would overflow the stack
+const MAX_DEPTH: usize = 256;
+
+impl Parser {
+    // Called for every operator, '(' and function call. Each operator in a chain like 1+2+3 counts as a level too, because the chain becomes a tree that deep.
+    fn descend(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(format!("formula is nested more than {} levels deep", MAX_DEPTH)));
+        }
+        Ok(())
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            position: self.position,
+            message,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    // skips whitespace, then consumes c if it is the next character
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn expression(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut left = self.term()?;
+        loop {
+            let operator = if self.eat('+') {
+                Operator::Add
+            } else if self.eat('-') {
+                Operator::Subtract
+            } else {
+                self.depth = depth;
+                return Ok(left);
+            };
+            self.descend()?;
+            left = Expr::Binary(operator, Box::new(left), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut left = self.factor()?;
+        loop {
+            let operator = if self.eat('*') {
+                Operator::Multiply
+            } else if self.eat('/') {
+                Operator::Divide
+            } else {
+                self.depth = depth;
+                return Ok(left);
+            };
+            self.descend()?;
+            left = Expr::Binary(operator, Box::new(left), Box::new(self.factor()?));
+        }
+    }
+
+    fn factor(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        if self.eat('-') {
+            self.descend()?;
+            let inner = self.factor()?;
+            self.depth = depth;
+            return Ok(Expr::Negate(Box::new(inner)));
+        }
+        if self.eat('(') {
+            self.descend()?;
+            let inner = self.expression()?;
+            if !self.eat(')') {
+                return Err(self.error(String::from("expected ')'")));
+            }
+            self.depth = depth;
+            return Ok(inner);
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let start = self.position;
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                number.parse().map(Expr::Number).map_err(|_| ParseError {
+                    position: start,
+                    message: format!("invalid number {:?}", number),
+                })
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let start = self.position;
+                let name = self.take_while(|c| c.is_ascii_alphanumeric());
+                if self.eat('(') {
+                    let function = match name.to_ascii_uppercase().as_str() {
+                        "SUM" => Function::Sum,
+                        "AVERAGE" => Function::Average,
+                        "MIN" => Function::Min,
+                        "MAX" => Function::Max,
+                        _ => {
+                            return Err(ParseError {
+                                position: start,
+                                message: format!("unknown function {}", name),
+                            })
+                        }
+                    };
+                    self.descend()?;
+                    let args = self.arguments()?;
+                    self.depth = depth;
+                    return Ok(Expr::Call(function, args));
+                }
+                name.parse().map(Expr::Cell).map_err(|_| ParseError {
+                    position: start,
+                    message: format!("invalid cell reference {:?}", name),
+                })
+            }
+            Some(c) => Err(self.error(format!("unexpected {:?}", c))),
+            None => Err(self.error(String::from("unexpected end of formula"))),
+        }
+    }
+
+    // the opening '(' has already been consumed
+    fn arguments(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut args = Vec::new();
+        if self.eat(')') {
+            return Ok(args);
+        }
+        loop {
+            self.skip_whitespace();
+            let start = self.position;
+            let arg = self.expression()?;
+            let arg = match arg {
+                Expr::Cell(from) if self.eat(':') => match self.factor()? {
+                    Expr::Cell(to) if range_size(from, to) > MAX_RANGE_CELLS => {
+                        return Err(ParseError {
+                            position: start,
+                            message: format!("range {}:{} has more than {} cells", from, to, MAX_RANGE_CELLS),
+                        })
+                    }
+                    Expr::Cell(to) => Expr::Range(from, to),
+                    _ => return Err(self.error(String::from("expected a cell after ':'"))),
+                },
+                arg => arg,
+            };
+            args.push(arg);
+            if self.eat(')') {
+                return Ok(args);
+            }
+            if !self.eat(',') {
+                return Err(self.error(String::from("expected ',' or ')'")));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    DivisionByZero,
+    NotANumber(CellRef),
+}
+
+// The computed contents of a cell. Errors are values too: like in any spreadsheet, a formula that divides by zero shows an error in its own cell and in every cell that reads it, without stopping the rest of the sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Empty,
+    Number(f64),
+    Text(String),
+    Error(EvalError),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Empty => Ok(()),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Error(EvalError::DivisionByZero) => write!(f, "#DIV/0!"),
+            Value::Error(EvalError::NotANumber(_)) => write!(f, "#VALUE!"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SheetError {
+    InvalidAddress(String),
+    Parse { cell: CellRef, error: ParseError },
+    // the cells of the cycle in order, starting and ending with the cell being set
+    Cycle(Vec<CellRef>),
+    Csv { line: usize, message: String },
+    // zero-based, like CellRef::new takes them
+    OutOfBounds { column: u32, row: u32 },
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SheetError::InvalidAddress(address) => write!(f, "{:?} is not a cell address", address),
+            SheetError::Parse { cell, error } => write!(f, "invalid formula in {}: {}", cell, error),
+            SheetError::Cycle(cells) => write!(f, "circular reference: {}", join_refs(cells, " -> ")),
+            SheetError::Csv { line, message } => write!(f, "CSV line {}: {}", line, message),
+            SheetError::OutOfBounds { column, row } => write!(f, "column {} row {} is outside the sheet", column, row),
+        }
+    }
+}
+
+impl std::error::Error for SheetError {}
+
+#[derive(Default)]
+pub struct Sheet {
+    cells: HashMap<CellRef, SpreadSheetCell>,
+    values: HashMap<CellRef, Value>,
+    // the cells each formula reads, and the reverse: the formulas that read each cell
+    dependencies: HashMap<CellRef, Vec<CellRef>>,
+    dependents: HashMap<CellRef, HashSet<CellRef>>,
+}
+
+impl Sheet {
+    pub fn new() -> Sheet {
+        Sheet::default()
+    }
+
+    pub fn value(&self, cell: CellRef) -> Value {
+        self.values.get(&cell).cloned().unwrap_or(Value::Empty)
+    }
+
+    // Turns user input into a cell the way a spreadsheet does: '=' starts a formula, numbers become Int or FLoat, anything else is text.
+    // A leading apostrophe forces text, so '=A1 and '42 are stored as the text "=A1" and "42".
+    pub fn set_input(&mut self, address: &str, input: &str) -> Result<Vec<CellRef>, SheetError> {
+        let cell: CellRef = address.parse()?;
+        let content = if let Some(text) = input.strip_prefix('\'') {
+            SpreadSheetCell::Text(text.to_string())
+        } else if input.starts_with('=') {
+            let formula = Formula::parse(input).map_err(|error| SheetError::Parse { cell, error })?;
+            SpreadSheetCell::Formula(formula)
+        } else if let Ok(int) = input.trim().parse::<i32>() {
+            SpreadSheetCell::Int(int)
+        } else if let Ok(float) = input.trim().parse::<f64>() {
+            SpreadSheetCell::FLoat(float)
+        } else {
+            SpreadSheetCell::Text(input.to_string())
+        };
+        self.set(cell, content)
+    }
+
+    // Stores the cell and recalculates it and everything that depends on it, returning the cells in the order they were recalculated.
+    // A formula that would create a cycle is rejected and the sheet is left unchanged.
+    pub fn set(&mut self, cell: CellRef, content: SpreadSheetCell) -> Result<Vec<CellRef>, SheetError> {
+        let mut new_dependencies = Vec::new();
+        if let SpreadSheetCell::Formula(formula) = &content {
+            formula.expr.references(&mut new_dependencies);
+        }
+        new_dependencies.sort();
+        new_dependencies.dedup();
+
+        if let Some(cycle) = self.find_cycle(cell, &new_dependencies) {
+            return Err(SheetError::Cycle(cycle));
+        }
+
+        for old in self.dependencies.remove(&cell).unwrap_or_default() {
+            if let Some(readers) = self.dependents.get_mut(&old) {
+                readers.remove(&cell);
+            }
+        }
+        for dependency in &new_dependencies {
+            self.dependents.entry(*dependency).or_default().insert(cell);
+        }
+        if !new_dependencies.is_empty() {
+            self.dependencies.insert(cell, new_dependencies);
+        }
+        self.cells.insert(cell, content);
+
+        let order = self.recalculation_order(cell);
+        for cell in &order {
+            let value = self.evaluate_cell(*cell);
+            self.values.insert(*cell, value);
+        }
+        Ok(order)
+    }
+
+    // Depth-first search through the dependencies of the new formula. Reaching the cell being set means the formula would (indirectly) read itself.
+    fn find_cycle(&self, cell: CellRef, new_dependencies: &[CellRef]) -> Option<Vec<CellRef>> {
+        let mut visited = HashSet::new();
+        for start in new_dependencies {
+            let mut path = vec![cell];
+            if self.path_to(*start, cell, &mut visited, &mut path) {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    fn path_to(&self, from: CellRef, target: CellRef, visited: &mut HashSet<CellRef>, path: &mut Vec<CellRef>) -> bool {
+        path.push(from);
+        if from == target {
+            return true;
+        }
+        if visited.insert(from) {
+            for next in self.dependencies.get(&from).into_iter().flatten() {
+                if self.path_to(*next, target, visited, path) {
+                    return true;
+                }
+            }
+        }
+        path.pop();
+        false
+    }
+
+    // Kahn's algorithm over the changed cell and everything that depends on it, directly or indirectly.
+    // A cell is only recalculated once all of the affected cells it reads are done; the cells that are ready wait in a min-heap, so ties are broken in row order and the result is deterministic.
+    fn recalculation_order(&self, changed: CellRef) -> Vec<CellRef> {
+        let mut affected = HashSet::from([changed]);
+        let mut queue = VecDeque::from([changed]);
+        while let Some(cell) = queue.pop_front() {
+            for reader in self.dependents.get(&cell).into_iter().flatten() {
+                if affected.insert(*reader) {
+                    queue.push_back(*reader);
+                }
+            }
+        }
+
+        let mut waiting_on: HashMap<CellRef, usize> = affected
+            .iter()
+            .map(|cell| {
+                let count = self
+                    .dependencies
+                    .get(cell)
+                    .map_or(0, |deps| deps.iter().filter(|d| affected.contains(d)).count());
+                (*cell, count)
+            })
+            .collect();
+        let mut ready: BinaryHeap<Reverse<CellRef>> = waiting_on
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(cell, _)| Reverse(*cell))
+            .collect();
+
+        let mut order = Vec::new();
+        while let Some(Reverse(cell)) = ready.pop() {
+            order.push(cell);
+            for reader in self.dependents.get(&cell).into_iter().flatten() {
+                if let Some(count) = waiting_on.get_mut(reader) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(Reverse(*reader));
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    fn evaluate_cell(&self, cell: CellRef) -> Value {
+        match self.cells.get(&cell) {
+            None => Value::Empty,
+            Some(SpreadSheetCell::Int(int)) => Value::Number(f64::from(*int)),
+            Some(SpreadSheetCell::FLoat(float)) => Value::Number(*float),
+            Some(SpreadSheetCell::Text(text)) => Value::Text(text.clone()),
+            Some(SpreadSheetCell::Formula(formula)) => match self.evaluate(&formula.expr) {
+                Ok(number) => Value::Number(number),
+                Err(error) => Value::Error(error),
+            },
+        }
+    }
+
+    // empty cells count as 0 in arithmetic; text can't be used as a number
+    fn number_at(&self, cell: CellRef) -> Result<f64, EvalError> {
+        match self.value(cell) {
+            Value::Empty => Ok(0.0),
+            Value::Number(n) => Ok(n),
+            Value::Text(_) => Err(EvalError::NotANumber(cell)),
+            Value::Error(error) => Err(error),
+        }
+    }
+
+    fn evaluate(&self, expr: &Expr) -> Result<f64, EvalError> {
+        match expr {
+            Expr::Number(n) => Ok(*n),
+            Expr::Cell(cell) => self.number_at(*cell),
+            // the parser only produces ranges inside function calls, where call() handles them
+            Expr::Range(from, _) => self.number_at(*from),
+            Expr::Negate(inner) => Ok(-self.evaluate(inner)?),
+            Expr::Binary(operator, left, right) => {
+                let (left, right) = (self.evaluate(left)?, self.evaluate(right)?);
+                match operator {
+                    Operator::Add => Ok(left + right),
+                    Operator::Subtract => Ok(left - right),
+                    Operator::Multiply => Ok(left * right),
+                    Operator::Divide if right == 0.0 => Err(EvalError::DivisionByZero),
+                    Operator::Divide => Ok(left / right),
+                }
+            }
+            Expr::Call(function, args) => self.call(*function, args),
+        }
+    }
+
+    // Inside a range, empty and text cells are skipped the way spreadsheets do, so SUM over a column with a heading still works
+    fn call(&self, function: Function, args: &[Expr]) -> Result<f64, EvalError> {
+        let mut numbers = Vec::new();
+        for arg in args {
+            match arg {
+                Expr::Range(from, to) => {
+                    for cell in range_cells(*from, *to) {
+                        match self.value(cell) {
+                            Value::Number(n) => numbers.push(n),
+                            Value::Error(error) => return Err(error),
+                            Value::Empty | Value::Text(_) => {}
+                        }
+                    }
+                }
+                arg => numbers.push(self.evaluate(arg)?),
+            }
+        }
+
+        let sum: f64 = numbers.iter().sum();
+        match function {
+            Function::Sum => Ok(sum),
+            Function::Average if numbers.is_empty() => Err(EvalError::DivisionByZero),
+            Function::Average => Ok(sum / numbers.len() as f64),
+            Function::Min => Ok(numbers.iter().copied().reduce(f64::min).unwrap_or(0.0)),
+            Function::Max => Ok(numbers.iter().copied().reduce(f64::max).unwrap_or(0.0)),
+        }
+    }
+
+    // Rows of the CSV become rows of the sheet, starting at A1. Every field goes through set_input, so "=A1*2" is imported as a formula.
+    pub fn from_csv(csv: &str) -> Result<Sheet, SheetError> {
+        let mut sheet = Sheet::new();
+        for (row, fields) in parse_csv(csv)?.into_iter().enumerate() {
+            for (column, field) in fields.iter().enumerate() {
+                if field.is_empty() {
+                    continue;
+                }
+                // a position that doesn't fit in a u32 is out of bounds as well
+                let to_u32 = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+                let cell = CellRef::new(to_u32(column), to_u32(row))?;
+                sheet.set_input(&cell.to_string(), field)?;
+            }
+        }
+        Ok(sheet)
+    }
+
+    // the cell contents as typed, formulas included, so from_csv(to_csv()) rebuilds the same sheet
+    pub fn to_csv(&self) -> String {
+        self.write_csv(|cell| match self.cells.get(&cell) {
+            None => String::new(),
+            Some(SpreadSheetCell::Int(int)) => int.to_string(),
+            // {:?} keeps the ".0" on whole floats, so 3.0 doesn't come back as an Int
+            Some(SpreadSheetCell::FLoat(float)) => format!("{:?}", float),
+            Some(SpreadSheetCell::Text(text)) if needs_apostrophe(text) => format!("'{}", text),
+            Some(SpreadSheetCell::Text(text)) => text.clone(),
+            Some(SpreadSheetCell::Formula(formula)) => format!("={}", formula.source),
+        })
+    }
+
+    // the computed values, the way a spreadsheet shows them
+    pub fn values_to_csv(&self) -> String {
+        self.write_csv(|cell| self.value(cell).to_string())
+    }
+
+    // Each row ends at its own last cell instead of filling out the whole bounding box, so one cell far to the right doesn't add thousands of empty fields to every row.
+    fn write_csv(&self, field: impl Fn(CellRef) -> String) -> String {
+        let mut last_columns: BTreeMap<u32, u32> = BTreeMap::new();
+        for cell in self.cells.keys() {
+            let last = last_columns.entry(cell.row).or_default();
+            *last = (*last).max(cell.column);
+        }
+        let mut csv = String::new();
+        let mut next_row = 0;
+        for (row, last_column) in last_columns {
+            for _ in next_row..row {
+                csv.push('\n');
+            }
+            let fields: Vec<String> = (0..=last_column)
+                .map(|column| quote_csv_field(&field(CellRef { row, column })))
+                .collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+            next_row = row + 1;
+        }
+        csv
+    }
+}
+
+// Text that set_input would read back as something else: a formula, a number, or text with its own leading apostrophe. An empty field isn't imported at all.
+fn needs_apostrophe(text: &str) -> bool {
+    text.is_empty() || text.starts_with(['=', '\'']) || text.trim().parse::<f64>().is_ok()
+}
+
+fn quote_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// A small CSV reader: fields are separated by commas, and a field in double quotes may contain commas, newlines and "" for a literal quote.
+fn parse_csv(csv: &str) -> Result<Vec<Vec<String>>, SheetError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut quote_line = 1;
+    let mut chars = csv.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.next_if_eq(&'"').is_some() => field.push('"'),
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            ('"', false) => {
+                return Err(SheetError::Csv {
+                    line,
+                    message: String::from("quote in the middle of an unquoted field"),
+                })
+            }
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+                line += 1;
+            }
+            (c, _) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if in_quotes {
+        return Err(SheetError::Csv {
+            line: quote_line,
+            message: String::from("unterminated quoted field"),
+        });
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(address: &str) -> CellRef {
+        address.parse().unwrap()
+    }
+
+    fn number(sheet: &Sheet, address: &str) -> f64 {
+        match sheet.value(cell(address)) {
+            Value::Number(n) => n,
+            other => panic!("{} is {:?}, not a number", address, other),
+        }
+    }
+
+    #[test]
+    fn parses_and_prints_addresses() {
+        for address in ["A1", "Z9", "AA10", "AZ3", "BA1", "ZZ100", "AAA1"] {
+            assert_eq!(cell(address).to_string(), address);
+        }
+        assert_eq!(cell("b2"), CellRef::new(1, 1).unwrap());
+        for invalid in ["", "A", "1", "A0", "1A", "A-1", "Ä1"] {
+            assert!(invalid.parse::<CellRef>().is_err(), "{:?} should be invalid", invalid);
+        }
+
+        // the last column and row still have an address, one past them doesn't
+        let last = CellRef::new(CellRef::MAX, CellRef::MAX).unwrap();
+        assert_eq!(last.to_string().parse::<CellRef>(), Ok(last));
+        assert_eq!(CellRef::new(u32::MAX, 0), Err(SheetError::OutOfBounds { column: u32::MAX, row: 0 }));
+        assert!(CellRef::new(0, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn evaluates_formulas_with_precedence_and_functions() {
+        let mut sheet = Sheet::new();
+        sheet.set_input("A1", "2").unwrap();
+        sheet.set_input("B2", "4").unwrap();
+        sheet.set_input("C1", "=A1+B2*3").unwrap();
+        sheet.set_input("C2", "=(A1+B2)*3").unwrap();
+        sheet.set_input("C3", "= -A1 - -1.5 / 3").unwrap();
+        sheet.set_input("D1", "=SUM(A1:B2)").unwrap();
+        sheet.set_input("D2", "=average(A1, B2, 6)").unwrap();
+        sheet.set_input("D3", "=MAX(A1:C2) - MIN(C1, 10)").unwrap();
+
+        assert_eq!(number(&sheet, "C1"), 14.0);
+        assert_eq!(number(&sheet, "C2"), 18.0);
+        assert_eq!(number(&sheet, "C3"), -1.5);
+        assert_eq!(number(&sheet, "D1"), 6.0);
+        assert_eq!(number(&sheet, "D2"), 4.0);
+        assert_eq!(number(&sheet, "D3"), 8.0);
+    }
+
+    #[test]
+    fn recalculates_dependents_in_topological_order() {
+        let mut sheet = Sheet::new();
+        sheet.set_input("A1", "1").unwrap();
+        sheet.set_input("C1", "=B1+B2").unwrap();
+        sheet.set_input("B2", "=A1*10").unwrap();
+        sheet.set_input("B1", "=A1+1").unwrap();
+        sheet.set_input("D1", "=C1+A1").unwrap();
+        sheet.set_input("E5", "=7").unwrap();
+
+        let order = sheet.set_input("A1", "5").unwrap();
+        assert_eq!(order, vec![cell("A1"), cell("B1"), cell("B2"), cell("C1"), cell("D1")]);
+        assert_eq!(number(&sheet, "C1"), 56.0);
+        assert_eq!(number(&sheet, "D1"), 61.0);
+
+        // once B1 no longer reads A1, changing A1 leaves it alone
+        sheet.set_input("B1", "100").unwrap();
+        let order = sheet.set_input("A1", "0").unwrap();
+        assert!(!order.contains(&cell("B1")));
+        assert_eq!(number(&sheet, "C1"), 100.0);
+    }
+
+    #[test]
+    fn rejects_cycles_and_keeps_the_sheet_unchanged() {
+        let mut sheet = Sheet::new();
+        sheet.set_input("A1", "=B1+1").unwrap();
+        sheet.set_input("B1", "=C1*2").unwrap();
+        sheet.set_input("C1", "3").unwrap();
+
+        let error = sheet.set_input("C1", "=A1").unwrap_err();
+        assert_eq!(error.to_string(), "circular reference: C1 -> A1 -> B1 -> C1");
+        assert_eq!(sheet.set_input("D1", "=D1"), Err(SheetError::Cycle(vec![cell("D1"), cell("D1")])));
+        assert!(matches!(sheet.set_input("E1", "=SUM(A1:E1)"), Err(SheetError::Cycle(_))));
+
+        assert_eq!(number(&sheet, "A1"), 7.0);
+        sheet.set_input("C1", "4").unwrap();
+        assert_eq!(number(&sheet, "A1"), 9.0);
+    }
+
+    #[test]
+    fn errors_stay_inside_the_sheet() {
+        let mut sheet = Sheet::new();
+        sheet.set_input("A1", "hello").unwrap();
+        sheet.set_input("A2", "=A1*2").unwrap();
+        sheet.set_input("A3", "=1/0").unwrap();
+        sheet.set_input("A4", "=A3+1").unwrap();
+        sheet.set_input("A5", "=SUM(A1, 2)").unwrap();
+        sheet.set_input("B1", "=SUM(A1:A2)").unwrap();
+
+        assert_eq!(sheet.value(cell("A2")), Value::Error(EvalError::NotANumber(cell("A1"))));
+        assert_eq!(sheet.value(cell("A4")).to_string(), "#DIV/0!");
+        assert_eq!(sheet.value(cell("A5")).to_string(), "#VALUE!");
+        assert_eq!(sheet.value(cell("B1")).to_string(), "#VALUE!");
+
+        let error = sheet.set_input("C1", "=A1 + FOO(2)").unwrap_err();
+        assert_eq!(error.to_string(), "invalid formula in C1: unknown function FOO at position 5");
+        assert!(sheet.set_input("C1", "=(1+2").is_err());
+        assert!(sheet.set_input("C1", "=1 2").is_err());
+        assert!(sheet.set_input("C1", "=A1:B2").is_err());
+
+        let error = sheet.set_input("C1", "=SUM(A1:ZZ100000)").unwrap_err();
+        assert_eq!(error.to_string(), "invalid formula in C1: range A1:ZZ100000 has more than 10000 cells at position 4");
+        assert!(sheet.set_input("C1", "=SUM(A2:J1001)").is_ok());
+
+        // deep nesting is a parse error, not a stack overflow
+        let deep = format!("={}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        let error = sheet.set_input("C1", &deep).unwrap_err();
+        assert_eq!(error.to_string(), "invalid formula in C1: formula is nested more than 256 levels deep at position 257");
+        assert!(sheet.set_input("C1", &format!("={}", "-".repeat(100_000))).is_err());
+        assert!(sheet.set_input("C1", &format!("=1{}", "+1".repeat(100_000))).is_err());
+        assert!(sheet.set_input("C1", &format!("=SUM({}1{})", "MAX(".repeat(300), ")".repeat(300))).is_err());
+        // a long chain inside the limit, and many parentheses one after the other, are fine
+        sheet.set_input("C1", &format!("=1{}", "+1".repeat(200))).unwrap();
+        assert_eq!(number(&sheet, "C1"), 201.0);
+        sheet.set_input("C1", &format!("=SUM({})", vec!["((1))"; 1000].join(","))).unwrap();
+        assert_eq!(number(&sheet, "C1"), 1000.0);
+    }
+
+    #[test]
+    fn round_trips_through_csv() {
+        let csv = "item,price,qty,total\n\"Shoes, red\",12.0,2,=B2*C2\n\"say \"\"hi\"\"\",3.5,,=SUM(D2:D2)+B3\n";
+        let sheet = Sheet::from_csv(csv).unwrap();
+        assert_eq!(sheet.to_csv(), csv);
+        assert_eq!(
+            sheet.values_to_csv(),
+            "item,price,qty,total\n\"Shoes, red\",12,2,24\n\"say \"\"hi\"\"\",3.5,,27.5\n"
+        );
+
+        assert_eq!(Sheet::from_csv("a,\"b\n").err().map(|e| e.to_string()), Some(String::from("CSV line 1: unterminated quoted field")));
+        assert!(Sheet::from_csv("a,b\"c\n").is_err());
+        assert!(Sheet::from_csv("=1+\n").is_err());
+    }
+
+    #[test]
+    fn text_that_looks_like_a_formula_or_number_survives_csv() {
+        let mut sheet = Sheet::new();
+        let texts = ["=A1+1", "42", " 3.5 ", "NaN", "'quoted", "", "plain"];
+        for (row, text) in texts.iter().enumerate() {
+            sheet.set(CellRef::new(0, row as u32).unwrap(), SpreadSheetCell::Text(text.to_string())).unwrap();
+        }
+        let csv = sheet.to_csv();
+        assert_eq!(csv, "'=A1+1\n'42\n' 3.5 \n'NaN\n''quoted\n'\nplain\n");
+
+        let imported = Sheet::from_csv(&csv).unwrap();
+        for (row, text) in texts.iter().enumerate() {
+            let cell = CellRef::new(0, row as u32).unwrap();
+            assert_eq!(imported.value(cell), Value::Text(text.to_string()), "row {}", row + 1);
+        }
+        assert_eq!(imported.to_csv(), csv);
+    }
+
+    #[test]
+    fn far_away_cells_only_cost_their_own_row() {
+        let mut sheet = Sheet::new();
+        sheet.set_input("A1", "1").unwrap();
+        sheet.set_input("ZZZZ3", "2").unwrap();
+        sheet.set_input("B4", "=A1+ZZZZ3").unwrap();
+
+        let csv = sheet.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "1");
+        assert_eq!(lines[1], "");
+        assert_eq!(lines[2].len(), cell("ZZZZ3").column as usize + 1);
+        assert_eq!(lines[3], ",=A1+ZZZZ3");
+        assert_eq!(Sheet::from_csv(&csv).unwrap().value(cell("B4")), Value::Number(3.0));
+    }
+}
@@ -0,0 +1,369 @@
+// Incremental statistics
+// oop::AveragedCollection shows encapsulation: the list and its average are private, so the average can't get out of sync with the list. But it sums the whole list again on every add and remove, only knows the mean, and its average of an empty list is NaN (0 / 0).
+// Statistics keeps the same encapsulation, but updates every statistic in O(log n) when a value is added or removed, and returns None when there is nothing to describe.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
+use std::hash::Hash;
+
+pub fn statistics() {
+    let mut stats: Statistics<i32> = [4, 8, 15, 16, 23, 42, 8].into_iter().collect();
+    print_statistics(&stats);
+
+    stats.remove(42);
+    stats.add(1);
+    print_statistics(&stats);
+
+    let empty: Statistics<u8> = Statistics::new();
+    println!("empty ({}): mean {:?}, median {:?}, mode {:?}", empty.is_empty(), empty.mean(), empty.median(), empty.mode());
+}
+
+fn print_statistics(stats: &Statistics<i32>) {
+    println!(
+        "{} values: mean {:?}, variance {:?}, std dev {:?}, min {:?}, max {:?}, median {:?}, mode {:?}",
+        stats.len(),
+        stats.mean(),
+        stats.variance(),
+        stats.std_dev(),
+        stats.min(),
+        stats.max(),
+        stats.median(),
+        stats.mode()
+    );
+}
+
+// The median is the top of one of two heaps: `low` is a max-heap holding the smaller half of the values and `high` a min-heap holding the larger half, with `low` allowed one extra value.
+// A BinaryHeap can only remove its top, so removing any other value is done lazily: the value is recorded in `removed` and only popped once it reaches the top of its heap. The lengths count only the values still present.
+struct MedianHeaps<T> {
+    low: BinaryHeap<T>,
+    high: BinaryHeap<Reverse<T>>,
+    low_len: usize,
+    high_len: usize,
+    removed: HashMap<T, usize>,
+}
+
+impl<T: Copy + Ord + Hash + Into<f64>> MedianHeaps<T> {
+    fn new() -> MedianHeaps<T> {
+        MedianHeaps {
+            low: BinaryHeap::new(),
+            high: BinaryHeap::new(),
+            low_len: 0,
+            high_len: 0,
+            removed: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, value: T) {
+        if self.low.peek().is_none_or(|top| value <= *top) {
+            self.low.push(value);
+            self.low_len += 1;
+        } else {
+            self.high.push(Reverse(value));
+            self.high_len += 1;
+        }
+        self.rebalance();
+    }
+
+    // the caller makes sure the value is present
+    fn remove(&mut self, value: T) {
+        *self.removed.entry(value).or_insert(0) += 1;
+        // every value in `low` is <= every value in `high`, so this is the heap the value lives in
+        if self.low.peek().is_some_and(|top| value <= *top) {
+            self.low_len -= 1;
+        } else {
+            self.high_len -= 1;
+        }
+        self.prune();
+        self.rebalance();
+    }
+
+    fn rebalance(&mut self) {
+        if self.low_len > self.high_len + 1 {
+            if let Some(top) = self.low.pop() {
+                self.high.push(Reverse(top));
+                self.low_len -= 1;
+                self.high_len += 1;
+            }
+        } else if self.low_len < self.high_len {
+            if let Some(Reverse(top)) = self.high.pop() {
+                self.low.push(top);
+                self.high_len -= 1;
+                self.low_len += 1;
+            }
+        }
+        self.prune();
+    }
+
+    // pops removed values off the tops, so both tops are always values that are still present
+    fn prune(&mut self) {
+        while let Some(top) = self.low.peek() {
+            if !Self::take_removed(&mut self.removed, *top) {
+                break;
+            }
+            self.low.pop();
+        }
+        while let Some(Reverse(top)) = self.high.peek() {
+            if !Self::take_removed(&mut self.removed, *top) {
+                break;
+            }
+            self.high.pop();
+        }
+    }
+
+    fn take_removed(removed: &mut HashMap<T, usize>, value: T) -> bool {
+        match removed.get_mut(&value) {
+            Some(count) if *count > 1 => *count -= 1,
+            Some(_) => {
+                removed.remove(&value);
+            }
+            None => return false,
+        }
+        true
+    }
+
+    fn median(&self) -> Option<f64> {
+        let low: f64 = (*self.low.peek()?).into();
+        if self.low_len > self.high_len {
+            return Some(low);
+        }
+        let high: f64 = self.high.peek()?.0.into();
+        Some((low + high) / 2.0)
+    }
+}
+
+// T can be any integer type that converts to f64 without losing precision (i32, u32, i16, u8, ...). f64 itself doesn't implement Ord or Hash, which the heaps and counts need.
+pub struct Statistics<T> {
+    len: usize,
+    mean: f64,
+    // sum of squared differences from the mean, kept up to date with Welford's algorithm
+    m2: f64,
+    // how often each value occurs; the first and last keys are the min and max
+    counts: BTreeMap<T, usize>,
+    // (count, value) pairs, so the last entry is the most common value. Reverse makes the smaller value win ties.
+    by_count: BTreeSet<(usize, Reverse<T>)>,
+    median: MedianHeaps<T>,
+}
+
+impl<T: Copy + Ord + Hash + Into<f64>> Default for Statistics<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + Ord + Hash + Into<f64>> Statistics<T> {
+    pub fn new() -> Statistics<T> {
+        Statistics {
+            len: 0,
+            mean: 0.0,
+            m2: 0.0,
+            counts: BTreeMap::new(),
+            by_count: BTreeSet::new(),
+            median: MedianHeaps::new(),
+        }
+    }
+
+    pub fn add(&mut self, value: T) {
+        let x: f64 = value.into();
+        self.len += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.len as f64;
+        self.m2 += delta * (x - self.mean);
+
+        let count = self.counts.entry(value).or_insert(0);
+        self.by_count.remove(&(*count, Reverse(value)));
+        *count += 1;
+        self.by_count.insert((*count, Reverse(value)));
+
+        self.median.add(value);
+    }
+
+    // Removes one occurrence of value. Unlike AveragedCollection::remove, which pops the last value pushed, any value can be removed; returns false if it isn't in the collection.
+    pub fn remove(&mut self, value: T) -> bool {
+        let count = match self.counts.get_mut(&value) {
+            Some(count) => count,
+            None => return false,
+        };
+        self.by_count.remove(&(*count, Reverse(value)));
+        *count -= 1;
+        if *count == 0 {
+            self.counts.remove(&value);
+        } else {
+            self.by_count.insert((*count, Reverse(value)));
+        }
+
+        // Welford's update run backwards
+        let x: f64 = value.into();
+        self.len -= 1;
+        if self.len == 0 {
+            self.mean = 0.0;
+            self.m2 = 0.0;
+        } else {
+            let delta = x - self.mean;
+            self.mean -= delta / self.len as f64;
+            self.m2 -= delta * (x - self.mean);
+        }
+
+        self.median.remove(value);
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.len > 0).then_some(self.mean)
+    }
+
+    // Population variance. Rounding errors can leave m2 a tiny bit below zero after removals, so it is clamped.
+    pub fn variance(&self) -> Option<f64> {
+        (self.len > 0).then(|| (self.m2 / self.len as f64).max(0.0))
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.counts.keys().next().copied()
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.counts.keys().next_back().copied()
+    }
+
+    // with an even number of values, the mean of the two middle ones
+    pub fn median(&self) -> Option<f64> {
+        self.median.median()
+    }
+
+    // the most common value; when several are equally common, the smallest of them
+    pub fn mode(&self) -> Option<T> {
+        self.by_count.last().map(|(_, Reverse(value))| *value)
+    }
+}
+
+impl<T: Copy + Ord + Hash + Into<f64>> Extend<T> for Statistics<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        for value in values {
+            self.add(value);
+        }
+    }
+}
+
+impl<T: Copy + Ord + Hash + Into<f64>> FromIterator<T> for Statistics<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut stats = Statistics::new();
+        stats.extend(values);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShiftRng;
+
+    fn close(a: Option<f64>, b: Option<f64>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => (a - b).abs() <= 1e-6 * (1.0 + b.abs()),
+            (a, b) => a == b,
+        }
+    }
+
+    // mean, variance, min, max, median and mode
+    type Summary = (Option<f64>, Option<f64>, Option<i32>, Option<i32>, Option<f64>, Option<i32>);
+
+    // the statistics recomputed from scratch the obvious way
+    fn naive(values: &[i32]) -> Summary {
+        if values.is_empty() {
+            return (None, None, None, None, None, None);
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+        let variance = values.iter().map(|v| (*v as f64 - mean).powi(2)).sum::<f64>() / n;
+
+        let mut sorted = values.to_vec();
+        sorted.sort();
+        let middle = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 1 {
+            sorted[middle] as f64
+        } else {
+            (sorted[middle - 1] + sorted[middle]) as f64 / 2.0
+        };
+
+        let mut counts = BTreeMap::new();
+        for v in values {
+            *counts.entry(*v).or_insert(0) += 1;
+        }
+        let highest = counts.values().max().copied();
+        let mode = counts.iter().find(|(_, count)| Some(**count) == highest).map(|(v, _)| *v);
+
+        (Some(mean), Some(variance), sorted.first().copied(), sorted.last().copied(), Some(median), mode)
+    }
+
+    #[test]
+    fn empty_collections_have_no_statistics() {
+        let mut stats: Statistics<i32> = Statistics::new();
+        assert!(stats.is_empty());
+        assert_eq!((stats.mean(), stats.variance(), stats.median()), (None, None, None));
+        assert_eq!((stats.min(), stats.max(), stats.mode()), (None, None, None));
+        assert!(!stats.remove(3));
+
+        stats.add(3);
+        assert!(stats.remove(3));
+        assert_eq!((stats.mean(), stats.median(), stats.mode()), (None, None, None));
+    }
+
+    #[test]
+    fn describes_a_small_collection() {
+        let mut stats: Statistics<u8> = [2, 4, 4, 4, 5, 5, 7, 9].into_iter().collect();
+        assert_eq!(stats.len(), 8);
+        assert_eq!(stats.mean(), Some(5.0));
+        assert_eq!(stats.variance(), Some(4.0));
+        assert_eq!(stats.std_dev(), Some(2.0));
+        assert_eq!((stats.min(), stats.max()), (Some(2), Some(9)));
+        assert_eq!(stats.median(), Some(4.5));
+        assert_eq!(stats.mode(), Some(4));
+
+        stats.remove(4);
+        stats.remove(9);
+        assert_eq!(stats.median(), Some(4.5));
+        // 4 and 5 both occur twice now; the smaller one wins
+        assert_eq!(stats.mode(), Some(4));
+        assert_eq!(stats.max(), Some(7));
+    }
+
+    #[test]
+    fn matches_naive_recomputation_after_random_updates() {
+        for seed in 1..=20 {
+            let mut rng = XorShiftRng::seed_from(seed);
+            let mut stats = Statistics::new();
+            let mut values: Vec<i32> = Vec::new();
+
+            for _ in 0..400 {
+                if !values.is_empty() && rng.next_f64() < 0.4 {
+                    let value = values.swap_remove((rng.next_u64() % values.len() as u64) as usize);
+                    assert!(stats.remove(value));
+                } else {
+                    // a narrow range, so there are plenty of duplicates
+                    let value = rng.range_f64(-30.0, 30.0) as i32;
+                    values.push(value);
+                    stats.add(value);
+                }
+
+                let (mean, variance, min, max, median, mode) = naive(&values);
+                assert_eq!(stats.len(), values.len());
+                assert!(close(stats.mean(), mean), "seed {}: mean {:?} != {:?}", seed, stats.mean(), mean);
+                assert!(close(stats.variance(), variance), "seed {}: variance {:?} != {:?}", seed, stats.variance(), variance);
+                assert_eq!((stats.min(), stats.max()), (min, max), "seed {}", seed);
+                assert_eq!(stats.median(), median, "seed {}", seed);
+                assert_eq!(stats.mode(), mode, "seed {}", seed);
+            }
+        }
+    }
+}
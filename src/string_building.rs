@@ -0,0 +1,309 @@
+// Building strings
+// collections::strings joins "tic", "tac" and "toe" with s1 + "-" + &s2 + "-" + &s3 and then with format!. Both work for a fixed number of pieces, but not when the pieces come from an iterator or a template only known at runtime.
+// This module has three tools for that: join for any iterator of Display values, a small template engine that fills in {name} placeholders, and a StringBuilder that collects pieces and allocates the final String once.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Write};
+use std::time::Instant;
+
+pub fn string_building() {
+    println!("{}", join(["tic", "tac", "toe"], "-"));
+    println!("{}", join(1..=5, ", "));
+    println!("{}", join_with_last(["Nairobi", "Kisumu", "Eldoret"], ", ", " and "));
+
+    let mut values = HashMap::new();
+    values.insert("name", String::from("Weldon"));
+    values.insert("count", 3.to_string());
+    let template = Template::parse("Hello {name}, you have {count} new messages. {{not a placeholder}}");
+    match template.and_then(|t| t.render(&values)) {
+        Ok(text) => println!("{}", text),
+        Err(error) => println!("template error: {}", error),
+    }
+    if let Err(error) = Template::parse("Bye {name}, see you {when}").and_then(|t| t.render(&values)) {
+        println!("template error: {}", error);
+    }
+
+    let mut builder = StringBuilder::new();
+    builder.push("tic").push("-").push(String::from("tac")).push("-").push("toe");
+    println!("built {:?} ({} bytes)", builder.build(), builder.len());
+
+    benchmark_builders();
+}
+
+// Writes each item with its Display implementation, putting the separator between items but not after the last one.
+// write! into a String can't actually fail, which is why the fmt::Result is ignored.
+pub fn join<I>(items: I, separator: &str) -> String
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let mut joined = String::new();
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            joined.push_str(separator);
+        }
+        let _ = write!(joined, "{}", item);
+    }
+    joined
+}
+
+// Like join, but the last two items are separated by last_separator: "a, b and c"
+pub fn join_with_last<I>(items: I, separator: &str, last_separator: &str) -> String
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let items: Vec<String> = items.into_iter().map(|item| item.to_string()).collect();
+    match items.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{}{}{}", join(rest, separator), last_separator, last),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TemplateError {
+    UnclosedPlaceholder { position: usize },
+    EmptyPlaceholder { position: usize },
+    UnmatchedClosingBrace { position: usize },
+    MissingKey(String),
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::UnclosedPlaceholder { position } => {
+                write!(f, "placeholder opened at byte {} is never closed", position)
+            }
+            TemplateError::EmptyPlaceholder { position } => {
+                write!(f, "placeholder at byte {} has no name", position)
+            }
+            TemplateError::UnmatchedClosingBrace { position } => {
+                write!(f, "'}}' at byte {} has no matching '{{' (write '}}}}' for a literal brace)", position)
+            }
+            TemplateError::MissingKey(name) => write!(f, "no value for placeholder {{{}}}", name),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    Placeholder(String),
+}
+
+// A template is parsed once into literal text and placeholders, then rendered as many times as needed.
+// {{ and }} stand for literal braces, the same escape format! uses.
+#[derive(Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|(_, next)| *next == '{').is_some() => literal.push('{'),
+                '}' if chars.next_if(|(_, next)| *next == '}').is_some() => literal.push('}'),
+                '}' => return Err(TemplateError::UnmatchedClosingBrace { position }),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => name.push(c),
+                            None => return Err(TemplateError::UnclosedPlaceholder { position }),
+                        }
+                    }
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return Err(TemplateError::EmptyPlaceholder { position });
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Placeholder(name.to_string()));
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+
+    pub fn render<V: Display>(&self, values: &HashMap<&str, V>) -> Result<String, TemplateError> {
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => rendered.push_str(text),
+                Part::Placeholder(name) => {
+                    let value = values
+                        .get(name.as_str())
+                        .ok_or_else(|| TemplateError::MissingKey(name.clone()))?;
+                    let _ = write!(rendered, "{}", value);
+                }
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+// Collects borrowed (&str) and owned (String) pieces without copying them, keeping a running total of their length.
+// build() then allocates a String of exactly the right size once and copies every piece into it, where repeated push_str may have to grow and copy the buffer several times along the way.
+// Cow ("clone on write") is an enum that holds either a borrowed or an owned value, so both kinds of pieces fit in the same Vec.
+pub struct StringBuilder<'a> {
+    pieces: Vec<Cow<'a, str>>,
+    len: usize,
+}
+
+impl<'a> Default for StringBuilder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> StringBuilder<'a> {
+    pub fn new() -> StringBuilder<'a> {
+        StringBuilder {
+            pieces: Vec::new(),
+            len: 0,
+        }
+    }
+
+    // returns &mut Self so calls can be chained: builder.push("a").push("b")
+    pub fn push(&mut self, piece: impl Into<Cow<'a, str>>) -> &mut Self {
+        let piece = piece.into();
+        self.len += piece.len();
+        self.pieces.push(piece);
+        self
+    }
+
+    // length in bytes of the string build() will produce
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn build(&self) -> String {
+        let mut built = String::with_capacity(self.len);
+        for piece in &self.pieces {
+            built.push_str(piece);
+        }
+        built
+    }
+}
+
+// Concatenates 200k short pieces three ways and prints how long each one took.
+// Don't expect the builder to win with pieces this small: String doubles its capacity when it grows, so push_str only copies the buffer about 20 times here, while the builder also pays for its Vec of pieces. The builder pays off when the pieces are large, and its result never has unused capacity.
+fn benchmark_builders() {
+    let words: Vec<String> = (0..200_000).map(|i| format!("word{} ", i)).collect();
+
+    let start = Instant::now();
+    let mut pushed = String::new();
+    for word in &words {
+        pushed.push_str(word);
+    }
+    let push_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut builder = StringBuilder::new();
+    for word in &words {
+        builder.push(word.as_str());
+    }
+    let built = builder.build();
+    let builder_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut added = String::new();
+    for word in &words {
+        added = added + word;
+    }
+    let add_time = start.elapsed();
+
+    println!(
+        "{} bytes: push_str {:?}, StringBuilder {:?}, + {:?} (same result: {})",
+        built.len(),
+        push_time,
+        builder_time,
+        add_time,
+        pushed == built && built == added
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_any_display_values() {
+        assert_eq!(join(["tic", "tac", "toe"], "-"), "tic-tac-toe");
+        assert_eq!(join(vec![1.5, 2.0], " / "), "1.5 / 2");
+        assert_eq!(join(Vec::<i32>::new(), ","), "");
+        assert_eq!(join(["only"], ","), "only");
+
+        assert_eq!(join_with_last(["a", "b", "c"], ", ", " and "), "a, b and c");
+        assert_eq!(join_with_last(["a", "b"], ", ", " or "), "a or b");
+        assert_eq!(join_with_last(["a"], ", ", " and "), "a");
+        assert_eq!(join_with_last(Vec::<&str>::new(), ", ", " and "), "");
+    }
+
+    #[test]
+    fn renders_templates() {
+        let values = HashMap::from([("name", "Weldon"), ("lang", "Rust")]);
+        let template = Template::parse("{name} writes { lang } {{and}} }} {name}").unwrap();
+        assert_eq!(template.render(&values).unwrap(), "Weldon writes Rust {and} } Weldon");
+
+        let numbers = HashMap::from([("x", 3)]);
+        assert_eq!(Template::parse("x = {x}").unwrap().render(&numbers).unwrap(), "x = 3");
+    }
+
+    #[test]
+    fn reports_template_errors() {
+        let values: HashMap<&str, &str> = HashMap::new();
+        assert_eq!(
+            Template::parse("hi {name}").unwrap().render(&values),
+            Err(TemplateError::MissingKey(String::from("name")))
+        );
+        assert_eq!(
+            Template::parse("hi {name").unwrap_err(),
+            TemplateError::UnclosedPlaceholder { position: 3 }
+        );
+        assert_eq!(
+            Template::parse("hi { }").unwrap_err(),
+            TemplateError::EmptyPlaceholder { position: 3 }
+        );
+        assert_eq!(
+            Template::parse("hi }").unwrap_err(),
+            TemplateError::UnmatchedClosingBrace { position: 3 }
+        );
+        assert_eq!(
+            TemplateError::MissingKey(String::from("name")).to_string(),
+            "no value for placeholder {name}"
+        );
+    }
+
+    #[test]
+    fn builder_matches_push_str() {
+        let owned = String::from("owned");
+        let mut builder = StringBuilder::new();
+        assert_eq!(builder.len(), 0);
+        builder.push("borrowed ").push(owned.clone()).push(" 日本");
+
+        let mut expected = String::new();
+        expected.push_str("borrowed ");
+        expected.push_str(&owned);
+        expected.push_str(" 日本");
+
+        assert_eq!(builder.build(), expected);
+        assert_eq!(builder.len(), expected.len());
+        assert_eq!(builder.build().capacity(), expected.len());
+    }
+}
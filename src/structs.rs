@@ -1,7 +1,10 @@
+use crate::comparator::{by, by_key};
+use crate::validation::Email;
+
 struct User{
     active:bool,
     user_name: String,
-    email:String,
+    email:Email,
     sign_in_count: u32,
 }
 
@@ -9,7 +12,7 @@ pub fn my_struct() {
     let mut user1 = User {
         active: true,
         user_name: String::from("weldon"),
-        email: "weldon@gmail.com".to_string(), // Convert the string literal to a String
+        email: Email::try_from("weldon@gmail.com").expect("a valid email address"), // Email checks the address when it is created
         sign_in_count: 1,
     };
 
@@ -28,6 +31,29 @@ pub fn my_struct() {
     println!("{}", user2.active);
     println!("{}", user2.email);
     println!("{}", user2.sign_in_count);
+
+    sort_users();
+}
+
+// active users first, then the ones who sign in most, then by name (see comparator.rs)
+fn sort_users() {
+    let mut users: Vec<User> = [("mylene", true, 4), ("weldon", false, 9), ("ada", true, 4), ("linus", true, 7)]
+        .into_iter()
+        .map(|(name, active, sign_in_count)| User {
+            active,
+            user_name: String::from(name),
+            email: format!("{}@example.com", name).parse().expect("a valid email address"),
+            sign_in_count,
+        })
+        .collect();
+    by_key(|u: &User| u.active)
+        .then_by(|u| u.sign_in_count)
+        .reversed()
+        .then(by(|a: &User, b: &User| a.user_name.cmp(&b.user_name)))
+        .sort(&mut users);
+    for user in &users {
+        println!("{} ({}, {} sign-ins)", user.user_name, user.email, user.sign_in_count);
+    }
 }
 
 
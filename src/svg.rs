@@ -0,0 +1,171 @@
+// SVG export
+// A second Renderer next to the character canvas: instead of filling in characters it writes SVG elements, so a screen can be saved as an image or put in a web page.
+// Components still think in character cells, so every cell becomes a CELL_WIDTH x CELL_HEIGHT box of pixels, and with a monospace font the SVG lines up with what the terminal shows.
+
+use std::fmt::Write;
+
+use crate::canvas::{Rect, Renderer};
+
+const CELL_WIDTH: u32 = 10;
+const CELL_HEIGHT: u32 = 20;
+
+// Pixels are computed in u64: a layout can hand out areas of up to u32::MAX cells, which would overflow a u32 once multiplied by the cell size
+fn pixels(cells: u32, cell_size: u32) -> u64 {
+    u64::from(cells) * u64::from(cell_size)
+}
+
+pub struct SvgRenderer {
+    width: u32,
+    height: u32,
+    elements: Vec<String>,
+}
+
+impl SvgRenderer {
+    // width and height in character cells, like Canvas::new
+    pub fn new(width: u32, height: u32) -> SvgRenderer {
+        SvgRenderer {
+            width,
+            height,
+            elements: Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        let (width, height) = (pixels(self.width, CELL_WIDTH), pixels(self.height, CELL_HEIGHT));
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"monospace\" font-size=\"16\">\n",
+            width, height, width, height
+        );
+        for element in &self.elements {
+            let _ = writeln!(svg, "  {}", element);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // the SVG inside a minimal HTML page, which a browser can open directly
+    pub fn finish_html(self, title: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{}</title></head>\n<body>\n{}</body>\n</html>\n",
+            escape(title),
+            self.finish()
+        )
+    }
+}
+
+impl Renderer for SvgRenderer {
+    // The canvas draws a border with characters in the outer cells, so the rectangle runs through the middle of those cells to land in the same place
+    fn border(&mut self, area: Rect) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        self.elements.push(format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"none\" stroke=\"black\"/>",
+            pixels(area.x, CELL_WIDTH) + u64::from(CELL_WIDTH / 2),
+            pixels(area.y, CELL_HEIGHT) + u64::from(CELL_HEIGHT / 2),
+            pixels(area.width - 1, CELL_WIDTH),
+            pixels(area.height - 1, CELL_HEIGHT)
+        ));
+    }
+
+    // y is the baseline of the text, a little above the bottom of the cell row
+    fn text(&mut self, x: u32, y: u32, text: &str, max_width: u32) {
+        let text: String = text.chars().take(max_width as usize).collect();
+        if text.trim().is_empty() {
+            return;
+        }
+        self.elements.push(format!(
+            "<text x=\"{}\" y=\"{}\" xml:space=\"preserve\">{}</text>",
+            pixels(x, CELL_WIDTH),
+            pixels(y, CELL_HEIGHT) + u64::from(CELL_HEIGHT * 3 / 4),
+            escape(&text)
+        ));
+    }
+}
+
+// a label like "Save & <quit>" must not be read as markup
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oop::{Button, Checkbox, Draw, SelectBox, Screen};
+    use crate::ui::{Layout, Size, Ui};
+
+    #[test]
+    fn exports_components_as_svg() {
+        let button = Button { width: 8, height: 3, label: String::from("Save & <quit>"), on_click: None };
+        let mut svg = SvgRenderer::new(8, 3);
+        button.draw(&mut svg, Rect::new(0, 0, 8, 3));
+        assert_eq!(
+            svg.finish(),
+            "\
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"80\" height=\"60\" viewBox=\"0 0 80 60\" font-family=\"monospace\" font-size=\"16\">
+  <rect x=\"5\" y=\"10\" width=\"70\" height=\"40\" rx=\"3\" fill=\"none\" stroke=\"black\"/>
+  <text x=\"10\" y=\"35\" xml:space=\"preserve\">Save &amp;</text>
+</svg>
+"
+        );
+    }
+
+    #[test]
+    fn exports_a_whole_screen() {
+        let screen = Screen {
+            components: vec![SelectBox {
+                width: 10,
+                height: 4,
+                options: vec![String::from("Yes"), String::from("No")],
+                selected: Some(1),
+                on_change: None,
+            }],
+        };
+        let svg = screen.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"80\""));
+        assert!(svg.contains("<text x=\"20\" y=\"35\" xml:space=\"preserve\">Yes</text>"));
+        assert!(svg.contains("<text x=\"10\" y=\"55\" xml:space=\"preserve\">&gt;</text>"));
+        assert!(svg.contains("<text x=\"20\" y=\"55\" xml:space=\"preserve\">No</text>"));
+        assert_eq!(svg.matches("<rect").count(), 1);
+
+        let mut page = SvgRenderer::new(10, 1);
+        Checkbox { label: String::from("Café"), checked: true }.draw(&mut page, Rect::new(0, 0, 10, 1));
+        let html = page.finish_html("Form <1>");
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Form &lt;1&gt;</title>"));
+        assert!(html.contains(">[x] Café</text>\n</svg>\n</body>"));
+    }
+
+    #[test]
+    fn huge_layouts_export_without_overflowing() {
+        let select = SelectBox {
+            width: 10,
+            height: 4,
+            options: vec![String::from("Yes")],
+            selected: None,
+            on_change: None,
+        };
+        let layout = Layout::Row(vec![(Size::Flex(1), Layout::Component(0))]);
+        let svg = Ui::new(vec![select], layout, u32::MAX, u32::MAX).to_html("huge");
+        assert!(svg.contains("width=\"42949672950\" height=\"85899345900\""));
+        assert!(svg.contains("<rect x=\"5\" y=\"10\" width=\"42949672940\" height=\"85899345880\""));
+
+        let mut far = SvgRenderer::new(1, 1);
+        far.border(Rect::new(u32::MAX, u32::MAX, 1, 1));
+        far.text(u32::MAX, u32::MAX, "far", 3);
+        let svg = far.finish();
+        assert!(svg.contains("<rect x=\"42949672955\" y=\"85899345910\""));
+        assert!(svg.contains("<text x=\"42949672950\" y=\"85899345915\""));
+    }
+}
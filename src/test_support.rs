@@ -0,0 +1,27 @@
+// Helpers shared by the tests of several modules
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// A directory that is removed again, with everything in it, when it goes out of scope, using Drop just like CustomSmartPointer in smart_pointers.rs.
+// The process id in the name keeps two test runs at the same time from sharing a directory.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> TempDir {
+        let dir = std::env::temp_dir().join(format!("lesson2-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,393 @@
+// Text processing with string slices
+// slices::first_word finds the first word by looking for the ASCII byte b' '. That works for "Hello world" but not for "Hello,world", "Hello\tworld" or text in other scripts.
+// The functions here work on chars instead of bytes, so any whitespace or punctuation separates words. Like first_word, they return &str slices into the input instead of allocating new Strings, and the lifetime 'a ties every returned slice to the text it was cut from.
+
+use std::iter;
+use std::ops::{Bound, RangeBounds};
+
+pub fn text_processing() {
+    let text = "Call me Weldon. Some years ago—never mind how long—I went to sea! Did you?";
+
+    println!("words: {:?}", words(text).collect::<Vec<_>>());
+    println!("first: {:?}, third: {:?}, last: {:?}", first_word(text), nth_word(text, 2), last_word(text));
+    println!("sentences: {:?}", sentences(text).collect::<Vec<_>>());
+
+    let family = "family: 👨‍👩‍👧, flag: 🇰🇪, café";
+    println!(
+        "{:?} has {} words, {} chars and {} graphemes",
+        family,
+        word_count(family),
+        char_count(family),
+        grapheme_count(family)
+    );
+    println!("graphemes: {:?}", graphemes("e\u{301}🇰🇪👍🏽").collect::<Vec<_>>());
+
+    let greeting = "Jambo 👋🏽 dünya, 你好!";
+    println!(
+        "chars 6..8: {:?}, graphemes 6..8: {:?}, out of range: {:?}",
+        substring_chars(greeting, 6..8),
+        substring_graphemes(greeting, 6..8),
+        substring_chars(greeting, 10..100)
+    );
+    println!("truncated: {:?}", truncate_chars(greeting, 12));
+    println!("windows: {:?}", char_windows("你好!", 2).collect::<Vec<_>>());
+}
+
+// Apostrophes belong to a word when they are inside it ("don't") but not when they quote it ("'hello'"), so they are trimmed from both ends of every word.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '\'' || c == '’' || is_combining_mark(c)
+}
+
+const APOSTROPHES: [char; 2] = ['\'', '’'];
+
+// An iterator over the words in a string. It only remembers the part of the text it hasn't looked at yet, so every item is a slice of the original input.
+pub struct Words<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            let start = self.rest.find(is_word_char)?;
+            let rest = &self.rest[start..];
+            let end = rest.find(|c| !is_word_char(c)).unwrap_or(rest.len());
+            self.rest = &rest[end..];
+
+            let word = rest[..end].trim_matches(&APOSTROPHES[..]);
+            if !word.is_empty() {
+                return Some(word);
+            }
+        }
+    }
+}
+
+pub fn words(text: &str) -> Words<'_> {
+    Words { rest: text }
+}
+
+// Every word together with the byte offset where it starts. Because each word is a slice of text, its offset is just the distance between the two pointers.
+pub fn word_indices(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    words(text).map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+pub fn first_word(text: &str) -> Option<&str> {
+    words(text).next()
+}
+
+// n counts from zero, like indexing a vector
+pub fn nth_word(text: &str, n: usize) -> Option<&str> {
+    words(text).nth(n)
+}
+
+pub fn last_word(text: &str) -> Option<&str> {
+    words(text).last()
+}
+
+pub fn word_count(text: &str) -> usize {
+    words(text).count()
+}
+
+// A char is one Unicode scalar value, which is not always what a reader sees as one character: "é" can be written as 'e' followed by a combining accent.
+pub fn char_count(text: &str) -> usize {
+    text.chars().count()
+}
+
+// Graphemes are the characters a reader actually sees.
+// The full rules (Unicode Annex #29) are long, so this is a simplified version covering the common cases: combining marks, variation selectors, emoji skin tones, emoji joined with a zero width joiner, flags made of two regional indicators, and "\r\n".
+pub struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+// accents and other marks that are drawn on top of the letter before them
+fn is_combining_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{0483}'..='\u{0489}'
+        | '\u{0591}'..='\u{05BD}'
+        | '\u{0610}'..='\u{061A}'
+        | '\u{064B}'..='\u{065F}'
+        | '\u{0900}'..='\u{0903}'
+        | '\u{093A}'..='\u{094F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+// characters that attach to the character before them instead of starting a new grapheme: combining marks, joiners, variation selectors, skin tones and emoji tags
+fn is_extend(c: char) -> bool {
+    is_combining_mark(c)
+        || matches!(c,
+            '\u{200C}'
+            | ZERO_WIDTH_JOINER
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0020}'..='\u{E007F}'
+        )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+        let mut previous = first;
+        let mut regional_indicators = usize::from(is_regional_indicator(first));
+        let mut end = self.rest.len();
+
+        for (i, c) in chars {
+            let joins = (previous == '\r' && c == '\n')
+                || is_extend(c)
+                || previous == ZERO_WIDTH_JOINER
+                || (is_regional_indicator(c) && regional_indicators % 2 == 1);
+            if !joins {
+                end = i;
+                break;
+            }
+            if is_regional_indicator(c) {
+                regional_indicators += 1;
+            }
+            previous = c;
+        }
+
+        let (grapheme, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(grapheme)
+    }
+}
+
+pub fn graphemes(text: &str) -> Graphemes<'_> {
+    Graphemes { rest: text }
+}
+
+pub fn grapheme_count(text: &str) -> usize {
+    graphemes(text).count()
+}
+
+// lifetime.rs finds the first sentence with novel.split('.').next(). Sentences does the same for every sentence, also ending them at '!', '?', '…' and the CJK full stop, and trims the whitespace around each one.
+pub struct Sentences<'a> {
+    rest: &'a str,
+}
+
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…' | '。')
+}
+
+impl<'a> Iterator for Sentences<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let end = self.rest.find(is_sentence_end).unwrap_or(self.rest.len());
+            let sentence = self.rest[..end].trim();
+            // skip the whole run of terminators, so "Really?!" ends one sentence instead of leaving an empty one behind
+            let after = &self.rest[end..];
+            self.rest = after.trim_start_matches(is_sentence_end);
+            if !sentence.is_empty() {
+                return Some(sentence);
+            }
+        }
+    }
+}
+
+pub fn sentences(text: &str) -> Sentences<'_> {
+    Sentences { rest: text }
+}
+
+// Safe substrings
+// Indexing a String with a range, like &s[0..5], uses byte offsets. If an offset lands inside a multi-byte character (é is 2 bytes, 日 is 3, most emoji are 4) the program panics.
+// These functions take char or grapheme positions instead and return None rather than panicking when the range doesn't fit the text. Ranges work like they do for slices: 2..5, ..3, 4.. and 1..=2 are all accepted.
+
+// Turns a range of positions into a range of byte offsets. offsets yields the byte offset where every char (or grapheme) starts, followed by text.len().
+fn slice_by_offsets(
+    text: &str,
+    mut offsets: impl Iterator<Item = usize>,
+    range: impl RangeBounds<usize>,
+) -> Option<&str> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => Some(end.checked_add(1)?),
+        Bound::Excluded(&end) => Some(end),
+        Bound::Unbounded => None,
+    };
+    if end.is_some_and(|end| end < start) {
+        return None;
+    }
+
+    let start_byte = offsets.nth(start)?;
+    let end_byte = match end {
+        // nth counts from the current position, which is just past start
+        Some(end) if end == start => start_byte,
+        Some(end) => offsets.nth(end - start - 1)?,
+        None => text.len(),
+    };
+    Some(&text[start_byte..end_byte])
+}
+
+pub fn substring_chars(text: &str, range: impl RangeBounds<usize>) -> Option<&str> {
+    let offsets = text.char_indices().map(|(i, _)| i).chain(iter::once(text.len()));
+    slice_by_offsets(text, offsets, range)
+}
+
+pub fn substring_graphemes(text: &str, range: impl RangeBounds<usize>) -> Option<&str> {
+    let offsets = graphemes(text)
+        .scan(0, |position, grapheme| {
+            let start = *position;
+            *position += grapheme.len();
+            Some(start)
+        })
+        .chain(iter::once(text.len()));
+    slice_by_offsets(text, offsets, range)
+}
+
+// The first max_chars chars of the text, or all of it when it is shorter
+pub fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+// Like the windows method on slices, but over chars: every run of `size` consecutive chars, each one a slice of the input.
+pub struct CharWindows<'a> {
+    rest: &'a str,
+    size: usize,
+}
+
+impl<'a> Iterator for CharWindows<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.size == 0 {
+            return None;
+        }
+        let mut ends = self.rest.char_indices().map(|(i, _)| i).chain(iter::once(self.rest.len()));
+        let window = &self.rest[..ends.nth(self.size)?];
+        // move forward by one whole char, however many bytes it takes
+        let first_len = window.chars().next().map_or(0, char::len_utf8);
+        self.rest = &self.rest[first_len..];
+        Some(window)
+    }
+}
+
+// a size of zero yields no windows at all
+pub fn char_windows(text: &str, size: usize) -> CharWindows<'_> {
+    CharWindows { rest: text, size }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_words_on_any_whitespace_and_punctuation() {
+        let text = "  Hello,world!\tHow's\n'life' — cafe\u{301} 👨‍👩‍👧 你好 (naïve)...";
+        assert_eq!(
+            words(text).collect::<Vec<_>>(),
+            vec!["Hello", "world", "How's", "life", "cafe\u{301}", "你好", "naïve"]
+        );
+        assert_eq!(first_word(text), Some("Hello"));
+        assert_eq!(nth_word(text, 2), Some("How's"));
+        assert_eq!(nth_word(text, 7), None);
+        assert_eq!(last_word(text), Some("naïve"));
+        assert_eq!(word_count(text), 7);
+        assert_eq!(first_word(" ,.! "), None);
+    }
+
+    #[test]
+    fn results_borrow_from_the_input() {
+        let novel = String::from("Call me Weldon. Some time back ...");
+        let word = nth_word(&novel, 2).unwrap();
+        // the slice points into novel's buffer rather than a copy
+        assert_eq!(word.as_ptr(), novel[8..].as_ptr());
+        assert_eq!(
+            word_indices("¡Hola, señor!").collect::<Vec<_>>(),
+            vec![(2, "Hola"), (8, "señor")]
+        );
+    }
+
+    #[test]
+    fn counts_chars_and_graphemes_differently() {
+        let decomposed = "e\u{301}";
+        assert_eq!(char_count(decomposed), 2);
+        assert_eq!(grapheme_count(decomposed), 1);
+
+        assert_eq!(graphemes("👨‍👩‍👧").count(), 1);
+        assert_eq!(char_count("👨‍👩‍👧"), 5);
+        assert_eq!(graphemes("🇰🇪🇺🇸").collect::<Vec<_>>(), vec!["🇰🇪", "🇺🇸"]);
+        assert_eq!(graphemes("👍🏽!\r\n").collect::<Vec<_>>(), vec!["👍🏽", "!", "\r\n"]);
+        assert_eq!(grapheme_count("日本語"), 3);
+        assert_eq!(grapheme_count(""), 0);
+    }
+
+    #[test]
+    fn splits_sentences() {
+        let text = "Call me Weldon. Some time back... Really?! 好。 Last one";
+        assert_eq!(
+            sentences(text).collect::<Vec<_>>(),
+            vec!["Call me Weldon", "Some time back", "Really", "好", "Last one"]
+        );
+        assert_eq!(sentences("...").next(), None);
+    }
+
+    #[test]
+    fn substrings_by_char_never_split_a_character() {
+        let text = "héllo 日本語 👋🏽!";
+        assert_eq!(substring_chars(text, 0..5), Some("héllo"));
+        assert_eq!(substring_chars(text, 6..9), Some("日本語"));
+        assert_eq!(substring_chars(text, 6..=7), Some("日本"));
+        assert_eq!(substring_chars(text, ..2), Some("hé"));
+        // the waving hand and its skin tone are two chars
+        assert_eq!(substring_chars(text, 10..11), Some("👋"));
+        assert_eq!(substring_chars(text, 10..), Some("👋🏽!"));
+        assert_eq!(substring_chars(text, 13..), Some(""));
+        assert_eq!(substring_chars(text, 3..3), Some(""));
+        assert_eq!(substring_chars(text, 14..), None);
+        assert_eq!(substring_chars(text, 5..20), None);
+        // a backwards range is a caller mistake, not a panic
+        #[allow(clippy::reversed_empty_ranges)]
+        let backwards = 4..2;
+        assert_eq!(substring_chars(text, backwards), None);
+        assert_eq!(substring_chars("", ..), Some(""));
+    }
+
+    #[test]
+    fn substrings_by_grapheme_keep_clusters_together() {
+        let text = "cafe\u{301} 👋🏽🇰🇪!";
+        assert_eq!(substring_graphemes(text, 0..4), Some("cafe\u{301}"));
+        assert_eq!(substring_graphemes(text, 5..6), Some("👋🏽"));
+        assert_eq!(substring_graphemes(text, 6..), Some("🇰🇪!"));
+        assert_eq!(substring_graphemes(text, 8..), Some(""));
+        assert_eq!(substring_graphemes(text, 9..), None);
+    }
+
+    #[test]
+    fn truncates_and_windows_by_char() {
+        assert_eq!(truncate_chars("日本語", 2), "日本");
+        assert_eq!(truncate_chars("日本語", 3), "日本語");
+        assert_eq!(truncate_chars("日本語", 10), "日本語");
+        assert_eq!(truncate_chars("añb", 0), "");
+
+        assert_eq!(char_windows("añ😀b", 2).collect::<Vec<_>>(), vec!["añ", "ñ😀", "😀b"]);
+        assert_eq!(char_windows("añ😀b", 4).collect::<Vec<_>>(), vec!["añ😀b"]);
+        assert_eq!(char_windows("añ😀b", 5).count(), 0);
+        assert_eq!(char_windows("añ😀b", 0).count(), 0);
+    }
+}
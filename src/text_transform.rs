@@ -0,0 +1,263 @@
+// Text transformations
+// The strings section in collections.rs ends by iterating over chars(). These transformations build on that: each one walks the text word by word (using text::words, the Unicode-aware version of slices::first_word) or char by char, and never cuts a multi-byte character in half.
+// They can also be run from the command line, for example: cargo run -- transform pig-latin "first apple"
+
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+
+use crate::text;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    PigLatin,
+    TitleCase,
+    SnakeCase,
+    CamelCase,
+    KebabCase,
+    ReverseWords,
+    Rot13,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 7] = [
+        Transform::PigLatin,
+        Transform::TitleCase,
+        Transform::SnakeCase,
+        Transform::CamelCase,
+        Transform::KebabCase,
+        Transform::ReverseWords,
+        Transform::Rot13,
+    ];
+
+    // the name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transform::PigLatin => "pig-latin",
+            Transform::TitleCase => "title",
+            Transform::SnakeCase => "snake",
+            Transform::CamelCase => "camel",
+            Transform::KebabCase => "kebab",
+            Transform::ReverseWords => "reverse-words",
+            Transform::Rot13 => "rot13",
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Transform::PigLatin => map_words(text, pig_latin_word),
+            Transform::TitleCase => map_words(text, capitalize),
+            Transform::SnakeCase => identifier_parts(text).join("_"),
+            Transform::KebabCase => identifier_parts(text).join("-"),
+            Transform::CamelCase => camel_case(text),
+            Transform::ReverseWords => reverse_words(text),
+            Transform::Rot13 => rot13(text),
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Transform::ALL
+            .into_iter()
+            .find(|t| t.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Transform::ALL.iter().map(|t| t.name()).collect();
+                format!("unknown transform {:?}, expected one of: {}", name, names.join(", "))
+            })
+    }
+}
+
+// Handles `transform <name> [text...]`. When no text is given it is read from standard input, so the command can be used in a pipe.
+pub fn run_command(args: &[String]) -> Result<String, String> {
+    let (name, words) = args
+        .split_first()
+        .ok_or_else(|| String::from("usage: transform <name> [text...]"))?;
+    let transform: Transform = name.parse()?;
+
+    let input = if words.is_empty() {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("could not read standard input: {}", e))?;
+        input
+    } else {
+        words.join(" ")
+    };
+    Ok(transform.apply(&input))
+}
+
+// Applies f to every word and copies everything between the words (spaces, punctuation) unchanged.
+fn map_words(input: &str, mut f: impl FnMut(&str) -> String) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut copied = 0;
+    for (start, word) in text::word_indices(input) {
+        result.push_str(&input[copied..start]);
+        result.push_str(&f(word));
+        copied = start + word.len();
+    }
+    result.push_str(&input[copied..]);
+    result
+}
+
+fn is_vowel(c: char) -> bool {
+    c.to_lowercase()
+        .all(|c| "aeiouáàâäãåéèêëíìîïóòôöõúùûüœæ".contains(c))
+}
+
+// The book's rules: the first consonant moves to the end and gets "ay" (first -> irst-fay), words starting with a vowel get "hay" (apple -> apple-hay).
+// The first letter is taken as a grapheme, so "ñandu" moves the whole "ñ" even when it is written as n plus a combining tilde.
+fn pig_latin_word(word: &str) -> String {
+    let first = match text::graphemes(word).next() {
+        Some(first) => first,
+        None => return String::new(),
+    };
+    match first.chars().next() {
+        Some(c) if !c.is_alphabetic() => word.to_string(),
+        Some(c) if is_vowel(c) => format!("{}-hay", word),
+        _ => format!("{}-{}ay", &word[first.len()..], first),
+    }
+}
+
+// to_uppercase returns an iterator because some characters become several: 'ß' uppercases to "SS"
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+// Splits text into lowercase parts for snake, kebab and camel case. Word boundaries are spaces and punctuation (so '_' and '-' too), plus the humps in camelCase and HTTPServer.
+fn identifier_parts(input: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for word in text::words(input) {
+        let chars: Vec<char> = word.chars().filter(|c| !matches!(c, '\'' | '’')).collect();
+        let mut current = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let hump = i > 0
+                && c.is_uppercase()
+                && (chars[i - 1].is_lowercase()
+                    || chars[i - 1].is_numeric()
+                    || (chars[i - 1].is_uppercase()
+                        && chars.get(i + 1).is_some_and(|next| next.is_lowercase())));
+            if hump && !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            current.extend(c.to_lowercase());
+        }
+        if !current.is_empty() {
+            parts.push(current);
+        }
+    }
+    parts
+}
+
+fn camel_case(input: &str) -> String {
+    let mut parts = identifier_parts(input).into_iter();
+    let first = parts.next().unwrap_or_default();
+    parts.fold(first, |mut camel, part| {
+        camel.push_str(&capitalize(&part));
+        camel
+    })
+}
+
+// Reverses the order of the whitespace-separated words, leaving every run of whitespace exactly where it was: "a  b\tc" -> "c  b\ta".
+fn reverse_words(input: &str) -> String {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut chars = input.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        let is_boundary = chars
+            .peek()
+            .is_none_or(|(_, next)| next.is_whitespace() != c.is_whitespace());
+        if is_boundary {
+            let end = chars.peek().map_or(input.len(), |(i, _)| *i);
+            runs.push(&input[start..end]);
+            start = end;
+        }
+    }
+
+    let is_word = |run: &&str| !run.starts_with(char::is_whitespace);
+    let mut reversed = runs.iter().filter(|run| is_word(run)).rev();
+    runs.iter()
+        .map(|run| if is_word(run) { reversed.next().unwrap_or(run) } else { run })
+        .copied()
+        .collect()
+}
+
+// ROT13 only rotates the 26 ASCII letters; everything else, including accented and non-Latin letters, passes through unchanged
+fn rot13(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => (((c as u8 - b'a') + 13) % 26 + b'a') as char,
+            'A'..='Z' => (((c as u8 - b'A') + 13) % 26 + b'A') as char,
+            _ => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pig_latin_follows_the_book_rules() {
+        let pig = |s| Transform::PigLatin.apply(s);
+        assert_eq!(pig("first apple"), "irst-fay apple-hay");
+        assert_eq!(pig("Hello, world! 42"), "ello-Hay, orld-way! 42");
+        assert_eq!(pig("n\u{303}andu éclair"), "andu-n\u{303}ay éclair-hay");
+        assert_eq!(pig("日本"), "本-日ay");
+    }
+
+    #[test]
+    fn changes_case() {
+        assert_eq!(Transform::TitleCase.apply("hello wORLD, straße ñandú"), "Hello World, Straße Ñandú");
+        assert_eq!(Transform::TitleCase.apply("ßig"), "SSig");
+
+        let input = "parseHTTPServer config-file_name Über größe";
+        assert_eq!(Transform::SnakeCase.apply(input), "parse_http_server_config_file_name_über_größe");
+        assert_eq!(Transform::KebabCase.apply(input), "parse-http-server-config-file-name-über-größe");
+        assert_eq!(Transform::CamelCase.apply(input), "parseHttpServerConfigFileNameÜberGröße");
+        assert_eq!(Transform::SnakeCase.apply("don't stop version2Beta"), "dont_stop_version2_beta");
+        assert_eq!(Transform::CamelCase.apply(""), "");
+    }
+
+    #[test]
+    fn reverses_words_but_keeps_whitespace() {
+        assert_eq!(Transform::ReverseWords.apply("one  two\tthree"), "three  two\tone");
+        assert_eq!(Transform::ReverseWords.apply(" 你好 世界 "), " 世界 你好 ");
+        assert_eq!(Transform::ReverseWords.apply("single"), "single");
+        assert_eq!(Transform::ReverseWords.apply(""), "");
+    }
+
+    #[test]
+    fn rot13_only_touches_ascii_letters() {
+        assert_eq!(Transform::Rot13.apply("Hello, Wörld!"), "Uryyb, Jöeyq!");
+        let text = "Why did the chicken cross the road? 🐔";
+        assert_eq!(Transform::Rot13.apply(&Transform::Rot13.apply(text)), text);
+    }
+
+    #[test]
+    fn runs_from_command_line_arguments() {
+        let args = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(run_command(&args(&["kebab", "Hello", "World"])), Ok(String::from("hello-world")));
+        for transform in Transform::ALL {
+            assert_eq!(transform.name().parse::<Transform>(), Ok(transform));
+        }
+        assert!(run_command(&args(&["shout", "hi"])).unwrap_err().contains("pig-latin"));
+        assert!(run_command(&[]).is_err());
+    }
+}
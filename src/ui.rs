@@ -0,0 +1,471 @@
+// Layout and events
+// oop::screen and oop::Screen<T> draw their components one below the other at the size each one asks for. A Layout describes something closer to a real window: rows and columns of components, padding around them, and components that take a fixed size or share the space that is left.
+// A Ui holds the components, their layout and which component has focus. Events (key presses and mouse clicks) go to the focused component through Draw::handle_event, so a whole interaction can be tested by feeding in events and looking at the rendered canvas, without a terminal.
+
+use crate::canvas::{Canvas, Rect, Renderer};
+use crate::oop::Draw;
+use crate::svg::SvgRenderer;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Tab,
+    BackTab,
+    Enter,
+    Backspace,
+    Up,
+    Down,
+    Char(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Key(Key),
+    // x and y are screen coordinates; the Ui makes them relative to the component before passing the click on
+    Click { x: u32, y: u32 },
+}
+
+// How much space a child of a Row or Column gets along the stack's direction (its height in a Column, its width in a Row).
+// Across the stack a child always gets the stack's full width or height.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Size {
+    // the child's own preferred size, for a component the width or height it was created with
+    Auto,
+    Fixed(u32),
+    // a share of the space left after the Auto and Fixed children: Flex(2) gets twice as much as Flex(1)
+    Flex(u32),
+}
+
+pub enum Layout {
+    // index of a component in the Ui's components
+    Component(usize),
+    Column(Vec<(Size, Layout)>),
+    Row(Vec<(Size, Layout)>),
+    Padding(u32, Box<Layout>),
+}
+
+impl Layout {
+    // the size this layout needs to show every component at its own size
+    pub fn preferred_size(&self, sizes: &[(u32, u32)]) -> (u32, u32) {
+        match self {
+            Layout::Component(index) => sizes.get(*index).copied().unwrap_or((0, 0)),
+            Layout::Column(children) => {
+                let (along, across) = Self::stack_size(children, sizes, |(width, height)| (height, width));
+                (across, along)
+            }
+            Layout::Row(children) => Self::stack_size(children, sizes, |size| size),
+            Layout::Padding(padding, child) => {
+                let (width, height) = child.preferred_size(sizes);
+                let padding = padding.saturating_mul(2);
+                (width.saturating_add(padding), height.saturating_add(padding))
+            }
+        }
+    }
+
+    // `axes` turns a (width, height) into (along the stack, across the stack), so Row and Column can share this
+    fn stack_size(children: &[(Size, Layout)], sizes: &[(u32, u32)], axes: impl Fn((u32, u32)) -> (u32, u32)) -> (u32, u32) {
+        children.iter().fold((0, 0), |(along, across), (size, child)| {
+            let (child_along, child_across) = axes(child.preferred_size(sizes));
+            let child_along = match size {
+                Size::Fixed(fixed) => *fixed,
+                Size::Auto | Size::Flex(_) => child_along,
+            };
+            (along.saturating_add(child_along), across.max(child_across))
+        })
+    }
+
+    // Gives every component in the layout its area inside `area`. Components the layout doesn't mention keep None and aren't drawn.
+    pub fn arrange(&self, area: Rect, sizes: &[(u32, u32)], areas: &mut [Option<Rect>]) {
+        match self {
+            Layout::Component(index) => {
+                if let Some(slot) = areas.get_mut(*index) {
+                    *slot = Some(area);
+                }
+            }
+            Layout::Padding(padding, child) => {
+                let inner = Rect::new(
+                    area.x.saturating_add(*padding),
+                    area.y.saturating_add(*padding),
+                    area.width.saturating_sub(padding.saturating_mul(2)),
+                    area.height.saturating_sub(padding.saturating_mul(2)),
+                );
+                child.arrange(inner, sizes, areas);
+            }
+            Layout::Column(children) => {
+                let lengths = Self::distribute(children, area.height, sizes, |(_, height)| height);
+                let mut y = area.y;
+                for ((_, child), height) in children.iter().zip(lengths) {
+                    child.arrange(Rect::new(area.x, y, area.width, height), sizes, areas);
+                    y = y.saturating_add(height);
+                }
+            }
+            Layout::Row(children) => {
+                let lengths = Self::distribute(children, area.width, sizes, |(width, _)| width);
+                let mut x = area.x;
+                for ((_, child), width) in children.iter().zip(lengths) {
+                    child.arrange(Rect::new(x, area.y, width, area.height), sizes, areas);
+                    x = x.saturating_add(width);
+                }
+            }
+        }
+    }
+
+    // Splits `available` between the children of a stack. Fixed and Auto children are served first, in order, and are cut short when there isn't enough room.
+    // Whatever is left goes to the Flex children by weight; the cells that don't divide evenly go to the first Flex children, one each.
+    fn distribute(children: &[(Size, Layout)], available: u32, sizes: &[(u32, u32)], along: impl Fn((u32, u32)) -> u32) -> Vec<u32> {
+        let mut remaining = available;
+        let mut lengths: Vec<u32> = children
+            .iter()
+            .map(|(size, child)| {
+                let wanted = match size {
+                    Size::Fixed(fixed) => *fixed,
+                    Size::Auto => along(child.preferred_size(sizes)),
+                    Size::Flex(_) => 0,
+                };
+                let length = wanted.min(remaining);
+                remaining -= length;
+                length
+            })
+            .collect();
+
+        let flex_weight = |size: &Size| if let Size::Flex(weight) = size { Some(*weight) } else { None };
+        // in u64, so large weights or a large window can't overflow
+        let total_weight: u64 = children.iter().filter_map(|(size, _)| flex_weight(size)).map(u64::from).sum();
+        if total_weight == 0 {
+            return lengths;
+        }
+        let share = |weight: u32| (u64::from(remaining) * u64::from(weight) / total_weight) as u32;
+        let mut extra = remaining - children.iter().filter_map(|(size, _)| flex_weight(size)).map(share).sum::<u32>();
+        for ((size, _), length) in children.iter().zip(lengths.iter_mut()) {
+            if let Some(weight) = flex_weight(size) {
+                *length = share(weight);
+                if extra > 0 && weight > 0 {
+                    *length += 1;
+                    extra -= 1;
+                }
+            }
+        }
+        lengths
+    }
+}
+
+// C is the component type: Box<dyn Draw> for a mix of components (from oop::screen), or one concrete type (from oop::Screen<T>)
+pub struct Ui<C: Draw> {
+    components: Vec<C>,
+    layout: Layout,
+    width: u32,
+    height: u32,
+    areas: Vec<Option<Rect>>,
+    focus: Option<usize>,
+}
+
+impl<C: Draw> Ui<C> {
+    // Focus starts on the first focusable component
+    pub fn new(components: Vec<C>, layout: Layout, width: u32, height: u32) -> Ui<C> {
+        let mut ui = Ui {
+            components,
+            layout,
+            width,
+            height,
+            areas: Vec::new(),
+            focus: None,
+        };
+        ui.arrange();
+        ui.focus = ui.focus_order().first().copied();
+        ui
+    }
+
+    // components only report their size through Draw::size, so the layout is computed again whenever it may have changed
+    fn arrange(&mut self) {
+        let sizes: Vec<(u32, u32)> = self.components.iter().map(|c| c.size()).collect();
+        self.areas = vec![None; self.components.len()];
+        self.layout.arrange(Rect::new(0, 0, self.width, self.height), &sizes, &mut self.areas);
+    }
+
+    // Tab moves through the components that are visible and focusable, in the order they were added
+    fn focus_order(&self) -> Vec<usize> {
+        (0..self.components.len())
+            .filter(|i| self.areas[*i].is_some() && self.components[*i].focusable())
+            .collect()
+    }
+
+    pub fn focused(&self) -> Option<usize> {
+        self.focus
+    }
+
+    pub fn render(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        self.draw(&mut canvas);
+        canvas
+    }
+
+    // the current state of the window as an SVG image in an HTML page
+    pub fn to_html(&self, title: &str) -> String {
+        let mut svg = SvgRenderer::new(self.width, self.height);
+        self.draw(&mut svg);
+        svg.finish_html(title)
+    }
+
+    fn draw(&self, renderer: &mut dyn Renderer) {
+        for (component, area) in self.components.iter().zip(&self.areas) {
+            if let Some(area) = area {
+                component.draw(renderer, *area);
+            }
+        }
+    }
+
+    // Routes one event and returns whether anything used it.
+    // Tab and BackTab move the focus. A click focuses the (focusable) component under the pointer and is passed to it with relative coordinates. Every other event goes to the focused component.
+    pub fn dispatch(&mut self, event: Event) -> bool {
+        let handled = match event {
+            Event::Key(Key::Tab) => self.move_focus(true),
+            Event::Key(Key::BackTab) => self.move_focus(false),
+            Event::Click { x, y } => {
+                let hit = self.areas.iter().enumerate().rev().find_map(|(i, area)| {
+                    area.filter(|a| x >= a.x && x - a.x < a.width && y >= a.y && y - a.y < a.height)
+                        .map(|a| (i, a))
+                });
+                match hit {
+                    Some((index, area)) => {
+                        if self.components[index].focusable() {
+                            self.focus = Some(index);
+                        }
+                        let relative = Event::Click { x: x - area.x, y: y - area.y };
+                        self.components[index].handle_event(&relative, (area.width, area.height))
+                    }
+                    None => false,
+                }
+            }
+            Event::Key(_) => match self.focus {
+                Some(index) => {
+                    let area = self.areas[index].unwrap_or(Rect::new(0, 0, 0, 0));
+                    self.components[index].handle_event(&event, (area.width, area.height))
+                }
+                None => false,
+            },
+        };
+        if handled {
+            self.arrange();
+        }
+        handled
+    }
+
+    fn move_focus(&mut self, forward: bool) -> bool {
+        let order = self.focus_order();
+        if order.is_empty() {
+            return false;
+        }
+        let current = self.focus.and_then(|f| order.iter().position(|i| *i == f));
+        let next = match (current, forward) {
+            (None, true) => 0,
+            (None, false) => order.len() - 1,
+            (Some(position), true) => (position + 1) % order.len(),
+            (Some(position), false) => (position + order.len() - 1) % order.len(),
+        };
+        self.focus = Some(order[next]);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oop::{Button, Checkbox, SelectBox, TextInput};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn select_box(log: &Rc<RefCell<Vec<String>>>) -> SelectBox {
+        let log = Rc::clone(log);
+        SelectBox {
+            width: 10,
+            height: 5,
+            options: vec![String::from("Red"), String::from("Green"), String::from("Blue")],
+            selected: None,
+            on_change: Some(Box::new(move |index, option| log.borrow_mut().push(format!("{} {}", index, option)))),
+        }
+    }
+
+    fn button(label: &str, log: &Rc<RefCell<Vec<String>>>) -> Button {
+        let (log, label_text) = (Rc::clone(log), label.to_string());
+        Button {
+            width: 8,
+            height: 3,
+            label: label.to_string(),
+            on_click: Some(Box::new(move || log.borrow_mut().push(format!("clicked {}", label_text)))),
+        }
+    }
+
+    #[test]
+    fn stacks_split_fixed_auto_and_flexible_space() {
+        let sizes = [(4, 1), (6, 2), (3, 3)];
+        let layout = Layout::Row(vec![
+            (Size::Auto, Layout::Component(0)),
+            (Size::Flex(1), Layout::Component(1)),
+            (Size::Fixed(5), Layout::Component(2)),
+            (Size::Flex(2), Layout::Padding(1, Box::new(Layout::Column(vec![])))),
+        ]);
+        let mut areas = vec![None; 3];
+        layout.arrange(Rect::new(0, 0, 20, 4), &sizes, &mut areas);
+        // 20 - 4 - 5 leaves 11 cells for weights 1 and 2: 3 and 7, plus the odd one for the first
+        assert_eq!(areas[0], Some(Rect::new(0, 0, 4, 4)));
+        assert_eq!(areas[1], Some(Rect::new(4, 0, 4, 4)));
+        assert_eq!(areas[2], Some(Rect::new(8, 0, 5, 4)));
+        assert_eq!(layout.preferred_size(&sizes), (4 + 6 + 5 + 2, 3));
+
+        let column = Layout::Padding(1, Box::new(Layout::Column(vec![
+            (Size::Auto, Layout::Component(0)),
+            (Size::Auto, Layout::Component(1)),
+            (Size::Auto, Layout::Component(2)),
+        ])));
+        let mut areas = vec![None; 3];
+        column.arrange(Rect::new(0, 0, 10, 6), &sizes, &mut areas);
+        // not enough room: the last component only gets what is left
+        assert_eq!(areas, vec![Some(Rect::new(1, 1, 8, 1)), Some(Rect::new(1, 2, 8, 2)), Some(Rect::new(1, 4, 8, 1))]);
+        assert_eq!(column.preferred_size(&sizes), (8, 8));
+    }
+
+    #[test]
+    fn renders_a_form_layout() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let components: Vec<Box<dyn Draw>> = vec![
+            Box::new(TextInput { width: 10, placeholder: String::from("Name"), value: String::new() }),
+            Box::new(select_box(&log)),
+            Box::new(button("OK", &log)),
+            Box::new(button("Cancel", &log)),
+        ];
+        let layout = Layout::Padding(1, Box::new(Layout::Column(vec![
+            (Size::Auto, Layout::Component(0)),
+            (Size::Flex(1), Layout::Component(1)),
+            (Size::Auto, Layout::Row(vec![(Size::Flex(1), Layout::Component(2)), (Size::Flex(1), Layout::Component(3))])),
+        ])));
+        let ui = Ui::new(components, layout, 22, 13);
+        assert_eq!(
+            ui.render().to_string(),
+            "
+ +------------------+
+ |Name              |
+ +------------------+
+ +------------------+
+ | Red              |
+ | Green            |
+ | Blue             |
+ +------------------+
+ +--------++--------+
+ |   OK   || Cancel |
+ +--------++--------+
+"
+        );
+        assert_eq!(ui.focused(), Some(0));
+    }
+
+    #[test]
+    fn routes_events_to_the_focused_component() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let layout = Layout::Column(vec![
+            (Size::Auto, Layout::Component(0)),
+            (Size::Auto, Layout::Component(1)),
+            (Size::Auto, Layout::Row(vec![(Size::Auto, Layout::Component(2)), (Size::Auto, Layout::Component(3))])),
+        ]);
+        let components: Vec<Box<dyn Draw>> = vec![
+            Box::new(TextInput { width: 10, placeholder: String::from("Name"), value: String::new() }),
+            Box::new(select_box(&log)),
+            Box::new(button("OK", &log)),
+            Box::new(Checkbox { label: String::from("Me"), checked: false }),
+        ];
+        let mut ui = Ui::new(components, layout, 20, 11);
+
+        for c in "Bon".chars() {
+            ui.dispatch(Event::Key(Key::Char(c)));
+        }
+        ui.dispatch(Event::Key(Key::Backspace));
+        ui.dispatch(Event::Key(Key::Tab));
+        assert_eq!(ui.focused(), Some(1));
+        ui.dispatch(Event::Key(Key::Down));
+        ui.dispatch(Event::Key(Key::Down));
+        ui.dispatch(Event::Key(Key::Up));
+        // clicking the third row inside the select box picks "Blue"
+        ui.dispatch(Event::Click { x: 3, y: 6 });
+        ui.dispatch(Event::Key(Key::Tab));
+        assert!(ui.dispatch(Event::Key(Key::Enter)));
+        // a click anywhere on the button focuses and presses it
+        ui.dispatch(Event::Key(Key::BackTab));
+        assert!(ui.dispatch(Event::Click { x: 1, y: 9 }));
+        assert_eq!(ui.focused(), Some(2));
+        assert!(ui.dispatch(Event::Click { x: 9, y: 8 }));
+        assert_eq!(ui.focused(), Some(3));
+        assert!(!ui.dispatch(Event::Click { x: 19, y: 10 }));
+        assert!(!ui.dispatch(Event::Key(Key::Up)));
+
+        assert_eq!(*log.borrow(), vec!["0 Red", "1 Green", "0 Red", "2 Blue", "clicked OK", "clicked OK"]);
+        assert_eq!(
+            ui.render().to_string(),
+            "\
++------------------+
+|Bo                |
++------------------+
++------------------+
+| Red              |
+| Green            |
+|>Blue             |
++------------------+
++------+[x] Me
+|  OK  |
++------+"
+        );
+    }
+
+    #[test]
+    fn select_box_scrolls_inside_its_laid_out_height() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut select = select_box(&log);
+        select.height = 10;
+        select.options.extend([String::from("Cyan"), String::from("Gray")]);
+        // the layout only gives it 4 rows, so 2 options fit inside the border
+        let mut ui = Ui::new(vec![select], Layout::Column(vec![(Size::Fixed(4), Layout::Component(0))]), 10, 6);
+
+        for _ in 0..3 {
+            ui.dispatch(Event::Key(Key::Down));
+        }
+        assert_eq!(ui.render().to_string(), "+--------+\n| Green  |\n|>Blue   |\n+--------+\n\n");
+        // the first row now shows Green, and the bottom border isn't an option
+        ui.dispatch(Event::Click { x: 2, y: 1 });
+        assert!(!ui.dispatch(Event::Click { x: 2, y: 3 }));
+        for _ in 0..5 {
+            ui.dispatch(Event::Key(Key::Down));
+        }
+        assert_eq!(ui.render().to_string(), "+--------+\n| Cyan   |\n|>Gray   |\n+--------+\n\n");
+        assert_eq!(*log.borrow(), vec!["0 Red", "1 Green", "2 Blue", "1 Green", "2 Blue", "3 Cyan", "4 Gray"]);
+    }
+
+    #[test]
+    fn huge_sizes_saturate_instead_of_overflowing() {
+        let sizes = [(u32::MAX, u32::MAX), (u32::MAX, 1)];
+        let layout = Layout::Padding(u32::MAX, Box::new(Layout::Row(vec![
+            (Size::Auto, Layout::Component(0)),
+            (Size::Flex(u32::MAX), Layout::Component(1)),
+            (Size::Flex(u32::MAX), Layout::Row(vec![])),
+        ])));
+        assert_eq!(layout.preferred_size(&sizes), (u32::MAX, u32::MAX));
+        let mut areas = vec![None; 2];
+        layout.arrange(Rect::new(u32::MAX - 1, 0, u32::MAX, u32::MAX), &sizes, &mut areas);
+        assert_eq!(areas[0].map(|a| a.x), Some(u32::MAX));
+
+        let mut canvas = Canvas::new(4, 2);
+        canvas.border(Rect::new(2, 0, u32::MAX, u32::MAX));
+        canvas.text(u32::MAX - 1, 1, "far away", 8);
+        assert_eq!(canvas.to_string(), "  +-\n  |");
+    }
+
+    #[test]
+    fn works_with_a_single_component_type() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let buttons = vec![button("A", &log), button("B", &log)];
+        let layout = Layout::Row(vec![(Size::Auto, Layout::Component(1)), (Size::Auto, Layout::Component(0))]);
+        let mut ui = Ui::new(buttons, layout, 16, 3);
+        assert_eq!(ui.render().to_string(), "+------++------+\n|  B   ||  A   |\n+------++------+");
+
+        ui.dispatch(Event::Key(Key::Enter));
+        ui.dispatch(Event::Key(Key::Tab));
+        ui.dispatch(Event::Key(Key::Char(' ')));
+        assert_eq!(*log.borrow(), vec!["clicked A", "clicked B"]);
+    }
+}
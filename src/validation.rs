@@ -0,0 +1,312 @@
+// Creating custom types for validation
+// Instead of checking that a value is valid in every function that receives it, we can check it once, when the value is created, and wrap it in a new type.
+// The field inside each type is private, so the only way to get an Email, Age, ShoeSize or Guess is through a constructor that validates it. Any function that receives one of these types can rely on it being valid without checking again.
+
+use std::error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    Empty {
+        what: &'static str,
+    },
+    NotANumber {
+        what: &'static str,
+        input: String,
+        source: ParseIntError,
+    },
+    OutOfRange {
+        what: &'static str,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+    InvalidEmail {
+        input: String,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::Empty { what } => write!(f, "{} must not be empty", what),
+            ValidationError::NotANumber { what, input, .. } => {
+                write!(f, "{} must be a whole number, got {:?}", what, input)
+            }
+            ValidationError::OutOfRange {
+                what,
+                value,
+                min,
+                max,
+            } => write!(f, "{} must be between {} and {}, got {}", what, min, max, value),
+            ValidationError::InvalidEmail { input, reason } => {
+                write!(f, "{:?} is not a valid email address: {}", input, reason)
+            }
+        }
+    }
+}
+
+impl error::Error for ValidationError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ValidationError::NotANumber { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn check_range(what: &'static str, value: i64, min: i64, max: i64) -> Result<(), ValidationError> {
+    if value < min || value > max {
+        return Err(ValidationError::OutOfRange {
+            what,
+            value,
+            min,
+            max,
+        });
+    }
+    Ok(())
+}
+
+// Parsing to i64 first means "-5" or "99999999999" are reported as out of range rather than as "not a number".
+fn parse_number(what: &'static str, input: &str) -> Result<i64, ValidationError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ValidationError::Empty { what });
+    }
+    trimmed
+        .parse::<i64>()
+        .map_err(|source| ValidationError::NotANumber {
+            what,
+            input: input.to_string(),
+            source,
+        })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Email(String);
+
+impl Email {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Email {
+    type Err = ValidationError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| ValidationError::InvalidEmail {
+            input: input.to_string(),
+            reason,
+        };
+        let email = input.trim();
+        if email.is_empty() {
+            return Err(ValidationError::Empty { what: "email" });
+        }
+        if email.chars().any(char::is_whitespace) {
+            return Err(invalid("it contains whitespace"));
+        }
+        let (local, domain) = email.split_once('@').ok_or_else(|| invalid("it is missing an '@'"))?;
+        if domain.contains('@') {
+            return Err(invalid("it contains more than one '@'"));
+        }
+        if local.is_empty() {
+            return Err(invalid("there is nothing before the '@'"));
+        }
+        if !domain.contains('.') || domain.split('.').any(str::is_empty) {
+            return Err(invalid("the domain must look like example.com"));
+        }
+        Ok(Email(email.to_string()))
+    }
+}
+
+impl TryFrom<&str> for Email {
+    type Error = ValidationError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Age(u32);
+
+impl Age {
+    pub const MAX: u32 = 150;
+
+    pub fn new(value: u32) -> Result<Age, ValidationError> {
+        check_range("age", value.into(), 0, Age::MAX.into())?;
+        Ok(Age(value))
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl FromStr for Age {
+    type Err = ValidationError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let value = parse_number("age", input)?;
+        check_range("age", value, 0, Age::MAX.into())?;
+        Ok(Age(value as u32))
+    }
+}
+
+impl TryFrom<&str> for Age {
+    type Error = ValidationError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShoeSize(u32);
+
+impl ShoeSize {
+    pub const MIN: u32 = 1;
+    pub const MAX: u32 = 25;
+
+    pub fn new(value: u32) -> Result<ShoeSize, ValidationError> {
+        check_range("shoe size", value.into(), ShoeSize::MIN.into(), ShoeSize::MAX.into())?;
+        Ok(ShoeSize(value))
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl FromStr for ShoeSize {
+    type Err = ValidationError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let value = parse_number("shoe size", input)?;
+        check_range("shoe size", value, ShoeSize::MIN.into(), ShoeSize::MAX.into())?;
+        Ok(ShoeSize(value as u32))
+    }
+}
+
+impl TryFrom<&str> for ShoeSize {
+    type Error = ValidationError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+// The guessing game from the book panics in Guess::new when the value is outside 1..=100; returning a Result lets the caller decide what to do instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guess(i32);
+
+impl Guess {
+    pub fn new(value: i32) -> Result<Guess, ValidationError> {
+        check_range("guess", value.into(), 1, 100)?;
+        Ok(Guess(value))
+    }
+
+    pub fn value(&self) -> i32 {
+        self.0
+    }
+}
+
+impl FromStr for Guess {
+    type Err = ValidationError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let value = parse_number("guess", input)?;
+        check_range("guess", value, 1, 100)?;
+        Ok(Guess(value as i32))
+    }
+}
+
+impl TryFrom<&str> for Guess {
+    type Error = ValidationError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShiftRng;
+
+    fn random_text(rng: &mut XorShiftRng) -> String {
+        let alphabet: Vec<char> = "ab1-@. \t9é+".chars().collect();
+        let len = rng.next_u64() % 12;
+        (0..len)
+            .map(|_| alphabet[(rng.next_u64() % alphabet.len() as u64) as usize])
+            .collect()
+    }
+
+    // property tests: whatever the input, a value is only ever constructed when it satisfies the type's invariant
+    #[test]
+    fn numbers_outside_the_range_never_construct() {
+        let mut rng = XorShiftRng::seed_from(29);
+        for _ in 0..5_000 {
+            let value = (rng.next_u64() % 400) as i64 - 100;
+            let text = value.to_string();
+
+            match Guess::try_from(text.as_str()) {
+                Ok(guess) => assert!((1..=100).contains(&guess.value())),
+                Err(_) => assert!(!(1..=100).contains(&value)),
+            }
+            match text.parse::<Age>() {
+                Ok(age) => assert!(age.value() <= Age::MAX),
+                Err(_) => assert!(!(0..=150).contains(&value)),
+            }
+            match text.parse::<ShoeSize>() {
+                Ok(size) => assert!((ShoeSize::MIN..=ShoeSize::MAX).contains(&size.value())),
+                Err(_) => assert!(!(1..=25).contains(&value)),
+            }
+        }
+    }
+
+    #[test]
+    fn random_text_only_constructs_valid_emails() {
+        let mut rng = XorShiftRng::seed_from(7);
+        for _ in 0..20_000 {
+            let input = random_text(&mut rng);
+            if let Ok(email) = Email::try_from(input.as_str()) {
+                let (local, domain) = email.as_str().split_once('@').unwrap();
+                assert!(!local.is_empty());
+                assert!(!domain.contains('@'));
+                assert!(domain.split('.').count() >= 2);
+                assert!(domain.split('.').all(|label| !label.is_empty()));
+                assert!(!email.as_str().contains(char::is_whitespace));
+            }
+            assert!(input.parse::<Guess>().is_err() || input.trim().parse::<i64>().is_ok());
+        }
+    }
+
+    #[test]
+    fn error_messages_explain_the_problem() {
+        assert_eq!(
+            Guess::new(101).unwrap_err().to_string(),
+            "guess must be between 1 and 100, got 101"
+        );
+        assert_eq!(
+            "ten".parse::<ShoeSize>().unwrap_err().to_string(),
+            "shoe size must be a whole number, got \"ten\""
+        );
+        assert_eq!("  ".parse::<Age>().unwrap_err().to_string(), "age must not be empty");
+        assert_eq!(
+            Email::try_from("weldon.gmail.com").unwrap_err().to_string(),
+            "\"weldon.gmail.com\" is not a valid email address: it is missing an '@'"
+        );
+        assert_eq!(Email::try_from(" weldon@gmail.com ").unwrap().as_str(), "weldon@gmail.com");
+    }
+}
@@ -0,0 +1,468 @@
+// Workout planner
+// generate_workout in closures.rs picks pushups or running from an intensity, and takes a break when a hard-coded "random" number happens to be 3.
+// WorkoutPlanner turns that into a plan for a whole week. Rest days are drawn from a seeded XorShiftRng, so the same seed always gives the same plan, and the amount of exercise follows the intensity.
+// Completed workouts are appended to a plain text log, one per line, which the `workout` command in cli.rs writes and reads back:
+//     cargo run -- workout plan 40 --seed 7
+//     cargo run -- workout log run 30
+//     cargo run -- workout history
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::rng::XorShiftRng;
+
+// below this intensity generate_workout only asks for pushups, and never gives a day off
+const LOW_INTENSITY: u32 = 25;
+const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const DEFAULT_LOG: &str = "workouts.log";
+const USAGE: &str = "usage: workout plan <intensity> [--seed <n>]
+       workout log (pushups <count> | run <minutes> | rest) [--file <path>]
+       workout history [--file <path>]";
+
+pub fn workout_planner() {
+    let mut planner = WorkoutPlanner::new(3);
+    for intensity in [10, 100] {
+        let plan = planner.plan_week(intensity);
+        println!("Intensity {} (rest days: {}):\n{}", intensity, plan.rest_days(), plan);
+    }
+    // a recovery week: just as hard, but with a day off every other day on average
+    let recovery = WorkoutPlanner::new(3).with_rest_chance(0.5).plan_week(100);
+    println!("Recovery week (rest days: {}):\n{}", recovery.rest_days(), recovery);
+
+    let path = std::env::temp_dir().join(format!("lesson2-workouts-{}.log", std::process::id()));
+    let log = WorkoutLog::new(&path);
+    let today = now().as_secs();
+    for workout in [Workout::Pushups { count: 12 }, Workout::Run { minutes: 95 }] {
+        if let Err(e) = log.record(&CompletedWorkout { timestamp: today, workout }) {
+            println!("could not log the workout: {}", e);
+        }
+    }
+    match log.entries() {
+        Ok(entries) => entries.iter().for_each(|entry| println!("{}", entry)),
+        Err(e) => println!("could not read the log: {}", e),
+    }
+    let _ = fs::remove_file(path);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Workout {
+    Pushups { count: u32 },
+    Run { minutes: u32 },
+    Rest,
+}
+
+// The same words are used in the log file and on the command line: "pushups 20", "run 30" or "rest"
+impl fmt::Display for Workout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Workout::Pushups { count } => write!(f, "pushups {}", count),
+            Workout::Run { minutes } => write!(f, "run {}", minutes),
+            Workout::Rest => write!(f, "rest"),
+        }
+    }
+}
+
+impl FromStr for Workout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let amount = |word: &str| {
+            word.parse::<u32>()
+                .map_err(|_| format!("{:?} is not a whole number", word))
+        };
+        match words[..] {
+            ["pushups", count] => Ok(Workout::Pushups { count: amount(count)? }),
+            ["run", minutes] => Ok(Workout::Run { minutes: amount(minutes)? }),
+            ["rest"] => Ok(Workout::Rest),
+            _ => Err(format!("unknown workout {:?}, expected \"pushups <count>\", \"run <minutes>\" or \"rest\"", s.trim())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeeklyPlan {
+    // Monday first
+    pub days: [Workout; 7],
+}
+
+impl WeeklyPlan {
+    pub fn rest_days(&self) -> usize {
+        self.days.iter().filter(|day| **day == Workout::Rest).count()
+    }
+}
+
+impl fmt::Display for WeeklyPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (name, workout)) in DAYS.iter().zip(&self.days).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}  {}", name, workout)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct WorkoutPlanner {
+    rng: XorShiftRng,
+    rest_chance: f64,
+}
+
+impl WorkoutPlanner {
+    // By default a hard week has about one rest day
+    pub fn new(seed: u64) -> WorkoutPlanner {
+        WorkoutPlanner {
+            rng: XorShiftRng::seed_from(seed),
+            rest_chance: 1.0 / 7.0,
+        }
+    }
+
+    // the chance, from 0 to 1, that any day of a hard week is a rest day
+    pub fn with_rest_chance(mut self, rest_chance: f64) -> WorkoutPlanner {
+        self.rest_chance = rest_chance;
+        self
+    }
+
+    // Each call continues the same random sequence, so planning several weeks in a row gives different weeks that are still reproducible from the seed.
+    pub fn plan_week(&mut self, intensity: u32) -> WeeklyPlan {
+        WeeklyPlan {
+            days: std::array::from_fn(|day| self.plan_day(day, intensity)),
+        }
+    }
+
+    // Like generate_workout: an easy week is pushups only. A hard week alternates running with pushups, and may have rest days.
+    fn plan_day(&mut self, day: usize, intensity: u32) -> Workout {
+        if intensity >= LOW_INTENSITY && self.rng.next_f64() < self.rest_chance {
+            return Workout::Rest;
+        }
+        let amount = self.vary(intensity);
+        if intensity < LOW_INTENSITY || day % 2 == 1 {
+            Workout::Pushups { count: amount }
+        } else {
+            Workout::Run { minutes: amount }
+        }
+    }
+
+    // up to 20% more or less than the intensity, so the days of a week aren't all the same
+    fn vary(&mut self, intensity: u32) -> u32 {
+        let amount = intensity as f64 * self.rng.range_f64(0.8, 1.2);
+        (amount.round() as u32).max(1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedWorkout {
+    // seconds since the Unix epoch
+    pub timestamp: u64,
+    pub workout: Workout,
+}
+
+impl fmt::Display for CompletedWorkout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}  {}", date(self.timestamp), self.workout)
+    }
+}
+
+#[derive(Debug)]
+pub enum WorkoutLogError {
+    Io(io::Error),
+    // line is counted from 1, like in an editor
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for WorkoutLogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorkoutLogError::Io(e) => write!(f, "{}", e),
+            WorkoutLogError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for WorkoutLogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorkoutLogError::Io(e) => Some(e),
+            WorkoutLogError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for WorkoutLogError {
+    fn from(error: io::Error) -> Self {
+        WorkoutLogError::Io(error)
+    }
+}
+
+// The log is a text file with one "<timestamp> <workout>" line per completed workout, so it can be read, edited or appended to by hand as well.
+pub struct WorkoutLog {
+    path: PathBuf,
+}
+
+impl WorkoutLog {
+    pub fn new(path: impl Into<PathBuf>) -> WorkoutLog {
+        WorkoutLog { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // The file is created on the first workout, and only ever appended to, so earlier entries survive even if the program is interrupted
+    pub fn record(&self, entry: &CompletedWorkout) -> Result<(), WorkoutLogError> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{} {}", entry.timestamp, entry.workout)?;
+        Ok(())
+    }
+
+    // every workout in the log, oldest first; a log that doesn't exist yet is simply empty
+    pub fn entries(&self) -> Result<Vec<CompletedWorkout>, WorkoutLogError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| parse_entry(line).map_err(|message| WorkoutLogError::Parse { line: i + 1, message }))
+            .collect()
+    }
+}
+
+fn parse_entry(line: &str) -> Result<CompletedWorkout, String> {
+    let (timestamp, workout) = line
+        .trim()
+        .split_once(' ')
+        .ok_or_else(|| format!("expected \"<timestamp> <workout>\", found {:?}", line))?;
+    Ok(CompletedWorkout {
+        timestamp: timestamp
+            .parse()
+            .map_err(|_| format!("{:?} is not a timestamp", timestamp))?,
+        workout: workout.parse()?,
+    })
+}
+
+// The calendar date (UTC) of a Unix timestamp, as YYYY-MM-DD.
+// The standard library has no calendar, so this is Howard Hinnant's civil_from_days: it counts in 400 year eras that start on March 1st, which puts the leap day at the end of the year.
+fn date(timestamp: u64) -> String {
+    let days = timestamp / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn now() -> std::time::Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+struct Options {
+    positional: Vec<String>,
+    seed: Option<u64>,
+    file: PathBuf,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        positional: Vec::new(),
+        seed: None,
+        file: PathBuf::from(DEFAULT_LOG),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" | "--file" => {
+                let value = args.next().ok_or_else(|| format!("{} needs a value\n{}", arg, USAGE))?;
+                if arg == "--seed" {
+                    options.seed = Some(value.parse().map_err(|_| format!("{:?} is not a valid seed", value))?);
+                } else {
+                    options.file = PathBuf::from(value);
+                }
+            }
+            _ => options.positional.push(arg.clone()),
+        }
+    }
+    Ok(options)
+}
+
+// The `workout` command; like text_transform::run_command it returns the text to print, or an error message
+pub fn run_command(args: &[String]) -> Result<String, String> {
+    let options = parse_options(args)?;
+    let log = WorkoutLog::new(&options.file);
+    let log_error = |e: WorkoutLogError| format!("{}: {}", log.path().display(), e);
+
+    match options.positional.split_first() {
+        Some((command, rest)) if command == "plan" => {
+            let intensity = match rest {
+                [intensity] => intensity
+                    .parse::<u32>()
+                    .map_err(|_| format!("{:?} is not a valid intensity", intensity))?,
+                _ => return Err(String::from(USAGE)),
+            };
+            // without a seed every run gives a new plan; the seed is printed so a plan worth keeping can be generated again
+            let seed = options.seed.unwrap_or_else(|| now().as_nanos() as u64);
+            let plan = WorkoutPlanner::new(seed).plan_week(intensity);
+            Ok(format!("Plan for intensity {} (seed {}):\n{}", intensity, seed, plan))
+        }
+        Some((command, rest)) if command == "log" => {
+            let workout: Workout = rest.join(" ").parse()?;
+            log.record(&CompletedWorkout { timestamp: now().as_secs(), workout }).map_err(log_error)?;
+            Ok(format!("Logged {}", workout))
+        }
+        Some((command, rest)) if command == "history" && rest.is_empty() => {
+            let entries = log.entries().map_err(log_error)?;
+            if entries.is_empty() {
+                return Ok(String::from("No workouts logged yet"));
+            }
+            // the log can be edited by hand, so the totals are u64: a few entries near u32::MAX can't overflow them
+            let (mut pushups, mut minutes) = (0u64, 0u64);
+            let mut lines = Vec::new();
+            for entry in &entries {
+                match entry.workout {
+                    Workout::Pushups { count } => pushups += u64::from(count),
+                    Workout::Run { minutes: run } => minutes += u64::from(run),
+                    Workout::Rest => {}
+                }
+                lines.push(entry.to_string());
+            }
+            lines.push(format!("{} workouts: {} pushups, {} minutes of running", entries.len(), pushups, minutes));
+            Ok(lines.join("\n"))
+        }
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn args(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_plan() {
+        let week = WorkoutPlanner::new(7).plan_week(40);
+        assert_eq!(week, WorkoutPlanner::new(7).plan_week(40));
+        assert_eq!(
+            week.to_string(),
+            "\
+Mon  run 47
+Tue  rest
+Wed  rest
+Thu  pushups 39
+Fri  run 35
+Sat  pushups 45
+Sun  run 44"
+        );
+
+        let mut planner = WorkoutPlanner::new(7);
+        let first = planner.plan_week(40);
+        assert_ne!(planner.plan_week(40), first);
+        assert_ne!(WorkoutPlanner::new(8).plan_week(40), first);
+    }
+
+    #[test]
+    fn plans_follow_the_intensity() {
+        for seed in 0..50 {
+            let easy = WorkoutPlanner::new(seed).plan_week(10);
+            assert_eq!(easy.rest_days(), 0);
+            for day in easy.days {
+                assert!(matches!(day, Workout::Pushups { count: 8..=12 }), "{:?}", day);
+            }
+
+            let hard = WorkoutPlanner::new(seed).plan_week(100);
+            for (i, day) in hard.days.iter().enumerate() {
+                match day {
+                    Workout::Run { minutes } => assert!(i % 2 == 0 && (80..=120).contains(minutes)),
+                    Workout::Pushups { count } => assert!(i % 2 == 1 && (80..=120).contains(count)),
+                    Workout::Rest => {}
+                }
+            }
+        }
+
+        assert_eq!(WorkoutPlanner::new(1).with_rest_chance(1.0).plan_week(30).rest_days(), 7);
+        assert_eq!(WorkoutPlanner::new(1).with_rest_chance(0.0).plan_week(30).rest_days(), 0);
+        assert_eq!(WorkoutPlanner::new(1).plan_week(0).days[0], Workout::Pushups { count: 1 });
+    }
+
+    #[test]
+    fn parses_workouts() {
+        for workout in [Workout::Pushups { count: 20 }, Workout::Run { minutes: 45 }, Workout::Rest] {
+            assert_eq!(workout.to_string().parse(), Ok(workout));
+        }
+        assert_eq!(" run   5 ".parse(), Ok(Workout::Run { minutes: 5 }));
+        assert!("swim 10".parse::<Workout>().unwrap_err().contains("unknown workout"));
+        assert!("pushups many".parse::<Workout>().unwrap_err().contains("\"many\" is not a whole number"));
+        assert!("rest 1".parse::<Workout>().is_err());
+    }
+
+    #[test]
+    fn records_workouts_in_a_file() {
+        let dir = TempDir::new("workout-log");
+        let file = dir.path().join("workouts.log");
+        let log = WorkoutLog::new(&file);
+        assert!(log.entries().unwrap().is_empty());
+
+        let entries = [
+            CompletedWorkout { timestamp: 1_760_832_000, workout: Workout::Run { minutes: 30 } },
+            CompletedWorkout { timestamp: 1_760_918_400, workout: Workout::Rest },
+        ];
+        for entry in &entries {
+            log.record(entry).unwrap();
+        }
+        assert_eq!(log.entries().unwrap(), entries);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "1760832000 run 30\n1760918400 rest\n");
+        assert_eq!(entries[0].to_string(), "2025-10-19  run 30");
+
+        fs::write(&file, "1760832000 run 30\n\nyesterday pushups 5\n").unwrap();
+        let error = log.entries().unwrap_err();
+        assert!(matches!(error, WorkoutLogError::Parse { line: 3, .. }), "{:?}", error);
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(4_102_444_799), "2099-12-31");
+    }
+
+    #[test]
+    fn generates_and_logs_sessions_from_the_command_line() {
+        let dir = TempDir::new("workout-cli");
+        let path = dir.path().join("workouts.log").to_string_lossy().into_owned();
+
+        let plan = run_command(&args(&["plan", "40", "--seed", "7"])).unwrap();
+        assert!(plan.starts_with("Plan for intensity 40 (seed 7):\nMon  run 47\nTue  rest\n"));
+        assert_eq!(run_command(&args(&["history", "--file", &path])), Ok(String::from("No workouts logged yet")));
+
+        assert_eq!(run_command(&args(&["log", "pushups", "20", "--file", &path])), Ok(String::from("Logged pushups 20")));
+        run_command(&args(&["log", "run", "15", "--file", &path])).unwrap();
+        run_command(&args(&["log", "pushups", "5", "--file", &path])).unwrap();
+        let history = run_command(&args(&["history", "--file", &path])).unwrap();
+        assert!(history.ends_with("\n3 workouts: 25 pushups, 15 minutes of running"), "{}", history);
+
+        assert!(run_command(&args(&["log", "swim", "--file", &path])).is_err());
+        assert!(run_command(&args(&["plan", "hard"])).unwrap_err().contains("not a valid intensity"));
+        assert!(run_command(&args(&["plan", "40", "--seed"])).unwrap_err().contains("--seed needs a value"));
+        assert_eq!(run_command(&args(&["stretch"])), Err(String::from(USAGE)));
+
+        // totals of hand-edited entries can go past u32::MAX
+        fs::write(&path, "1760832000 pushups 4294967295\n1760918400 pushups 4294967295\n1760918400 run 4294967295\n").unwrap();
+        let history = run_command(&args(&["history", "--file", &path])).unwrap();
+        assert!(history.ends_with("\n3 workouts: 8589934590 pushups, 4294967295 minutes of running"), "{}", history);
+    }
+}